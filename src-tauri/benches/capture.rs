@@ -0,0 +1,85 @@
+//! 截图路径的基准测试：对比复制像素的旧做法和共享像素/视图的做法
+//!
+//! 运行：cargo bench --bench capture
+
+use std::io::Cursor;
+use criterion::{black_box, criterion_group, criterion_main, Criterion};
+use image::codecs::png::CompressionType;
+use image::imageops::{self, FilterType};
+use image::{ImageBuffer, ImageOutputFormat, RgbaImage};
+use screenocr_tauri::history;
+use screenocr_tauri::screenshot::{self, CaptureRegion, ScreenCapture};
+
+/// 4K 截图，内容带渐变和细纹，PNG 压缩量接近真实桌面
+fn capture_4k() -> ScreenCapture {
+    let image = RgbaImage::from_fn(3840, 2160, |x, y| {
+        let stripe = if (x / 3 + y / 5) % 7 == 0 { 0 } else { 255 };
+        image::Rgba([(x % 256) as u8, (y % 256) as u8, stripe, 255])
+    });
+    ScreenCapture::from_image(image, 0, 0)
+}
+
+fn bench_clone(c: &mut Criterion) {
+    let capture = capture_4k();
+    let mut group = c.benchmark_group("clone");
+    group.bench_function("copy_pixels", |b| b.iter(|| black_box(capture.data.to_vec())));
+    group.bench_function("shared_pixels", |b| b.iter(|| black_box(capture.clone())));
+    group.finish();
+}
+
+fn bench_crop(c: &mut Criterion) {
+    let capture = capture_4k();
+    let region = CaptureRegion { x: 400, y: 300, width: 1200, height: 800 };
+    let mut group = c.benchmark_group("crop");
+    // 旧做法：先把整张截图复制成 ImageBuffer 再裁剪
+    group.bench_function("copy_then_crop", |b| {
+        b.iter(|| {
+            let image: RgbaImage =
+                ImageBuffer::from_raw(capture.width, capture.height, capture.data.to_vec()).unwrap();
+            black_box(imageops::crop_imm(&image, region.x, region.y, region.width, region.height).to_image())
+        })
+    });
+    group.bench_function("crop_region", |b| {
+        b.iter(|| black_box(screenshot::crop_region(&capture, &region).unwrap()))
+    });
+    group.finish();
+}
+
+fn bench_thumbnail(c: &mut Criterion) {
+    let capture = capture_4k();
+    let mut group = c.benchmark_group("thumbnail");
+    group.sample_size(20);
+    // 旧做法：复制整张截图后缩放，再编码 PNG
+    group.bench_function("copy_then_resize", |b| {
+        b.iter(|| {
+            let image: RgbaImage =
+                ImageBuffer::from_raw(capture.width, capture.height, capture.data.to_vec()).unwrap();
+            let thumbnail = imageops::resize(&image, 240, 135, FilterType::Triangle);
+            let mut buffer = Vec::new();
+            thumbnail.write_to(&mut Cursor::new(&mut buffer), ImageOutputFormat::Png).unwrap();
+            black_box(buffer)
+        })
+    });
+    group.bench_function("make_thumbnail", |b| b.iter(|| black_box(history::make_thumbnail(&capture).unwrap())));
+    group.finish();
+}
+
+/// 发给前端：Base64 放进 JSON，或者 capture:// 协议直接返回快速压缩的 PNG
+fn bench_transport(c: &mut Criterion) {
+    let capture = capture_4k();
+    let mut group = c.benchmark_group("transport");
+    group.sample_size(10);
+    group.bench_function("base64_json", |b| {
+        b.iter(|| {
+            let image_base64 = screenshot::to_base64_png(&capture).unwrap();
+            black_box(serde_json::to_vec(&serde_json::json!({ "image_base64": image_base64 })).unwrap())
+        })
+    });
+    group.bench_function("protocol_png", |b| {
+        b.iter(|| black_box(screenshot::encode_png(&capture, CompressionType::Fast).unwrap()))
+    });
+    group.finish();
+}
+
+criterion_group!(benches, bench_clone, bench_crop, bench_thumbnail, bench_transport);
+criterion_main!(benches);
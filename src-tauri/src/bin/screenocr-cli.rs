@@ -0,0 +1,310 @@
+//! ScreenOCR 命令行工具
+//!
+//! ```text
+//! screenocr-cli image.png
+//! cat image.png | screenocr-cli - --format json
+//! screenocr-cli --screen --engine PaddleOCR
+//! screenocr-cli --region -1920,0,800x600 --format tsv
+//! ```
+
+use anyhow::{Context, Result};
+use clap::{Parser, ValueEnum};
+use screenocr_tauri::config::{AppConfig, OcrEngine};
+use screenocr_tauri::ocr::{OcrRegistry, OcrResult};
+use screenocr_tauri::pipeline::{OcrPipeline, Source};
+use screenocr_tauri::screenshot::{self, ScreenCapture, ScreenRegion, ScreenSource, SystemScreenSource};
+use std::io::{Read, Write};
+use std::path::PathBuf;
+use std::process::ExitCode;
+use std::sync::Arc;
+
+/// 识别或截图失败
+const EXIT_FAILURE: u8 = 1;
+/// 参数错误（与 clap 的参数解析错误一致）
+const EXIT_USAGE: u8 = 2;
+
+/// 识别图片文件、标准输入或屏幕中的文字
+#[derive(Parser, Debug)]
+#[command(name = "screenocr-cli", version)]
+struct Cli {
+    /// 要识别的图片文件，`-` 表示从标准输入读取
+    inputs: Vec<String>,
+
+    /// 截取主屏幕进行识别
+    #[arg(long, conflicts_with = "region")]
+    screen: bool,
+
+    /// 截取虚拟桌面中的区域，格式为 X,Y,WIDTHxHEIGHT（坐标可以为负）
+    #[arg(long, value_parser = parse_region, allow_hyphen_values = true)]
+    region: Option<ScreenRegion>,
+
+    /// OCR 引擎，默认使用配置文件中的设置
+    #[arg(short, long)]
+    engine: Option<String>,
+
+    /// 识别语言，默认使用配置文件中的设置
+    #[arg(short, long)]
+    lang: Option<String>,
+
+    /// 输出格式
+    #[arg(short, long, value_enum, default_value_t = OutputFormat::Text)]
+    format: OutputFormat,
+
+    /// 跳过图像预处理
+    #[arg(long)]
+    no_preprocess: bool,
+
+    /// 配置文件路径，默认与桌面应用相同（也可以用 SCREENOCR_CONFIG 环境变量指定）
+    #[arg(long)]
+    config: Option<PathBuf>,
+
+    /// 列出可用的 OCR 引擎后退出
+    #[arg(long)]
+    list_engines: bool,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, ValueEnum)]
+enum OutputFormat {
+    /// 纯文本
+    Text,
+    /// 每个输入一行 JSON（OcrResult 结构）
+    Json,
+    /// 每个单词一行的制表符分隔表格，坐标为虚拟桌面/图片绝对坐标
+    Tsv,
+}
+
+/// 解析 `X,Y,WIDTHxHEIGHT`
+fn parse_region(value: &str) -> Result<ScreenRegion, String> {
+    let invalid = || format!("区域格式应为 X,Y,WIDTHxHEIGHT: {}", value);
+    let mut parts = value.splitn(3, ',');
+    let x = parts.next().and_then(|v| v.trim().parse().ok()).ok_or_else(invalid)?;
+    let y = parts.next().and_then(|v| v.trim().parse().ok()).ok_or_else(invalid)?;
+    let (width, height) = parts
+        .next()
+        .and_then(|size| size.split_once('x'))
+        .and_then(|(w, h)| Some((w.trim().parse().ok()?, h.trim().parse().ok()?)))
+        .ok_or_else(invalid)?;
+
+    if width == 0 || height == 0 {
+        return Err(invalid());
+    }
+    Ok(ScreenRegion { x, y, width, height })
+}
+
+/// 一个待识别的输入
+struct Input {
+    name: String,
+    capture: ScreenCapture,
+}
+
+#[tokio::main]
+async fn main() -> ExitCode {
+    let cli = Cli::parse();
+    match run(cli).await {
+        Ok(code) => code,
+        Err(e) => {
+            eprintln!("❌ {:#}", e);
+            ExitCode::from(EXIT_FAILURE)
+        }
+    }
+}
+
+async fn run(cli: Cli) -> Result<ExitCode> {
+    if let Some(path) = &cli.config {
+        AppConfig::set_config_path(path)?;
+    }
+    let mut config = load_config();
+    let registry = OcrRegistry::with_default_backends();
+    registry.configure(&config);
+
+    if cli.list_engines {
+        for engine in registry.describe() {
+            let status = if engine.capabilities.available { "可用" } else { "不可用" };
+            println!("{}\t{}\t{}", engine.name, status, engine.languages.join(","));
+        }
+        return Ok(ExitCode::SUCCESS);
+    }
+
+    if cli.inputs.is_empty() && !cli.screen && cli.region.is_none() {
+        eprintln!("❌ 请指定图片文件、`-`（标准输入）、--screen 或 --region");
+        return Ok(ExitCode::from(EXIT_USAGE));
+    }
+
+    // 命令行参数覆盖配置文件中的识别设置
+    if let Some(name) = &cli.engine {
+        match registry.get(name).ok().and_then(|_| OcrEngine::parse(name)) {
+            Some(engine) => config.ocr_engine = engine,
+            None => {
+                eprintln!("❌ 未知的 OCR 引擎: {}（可用引擎: {}）", name, registry.names().join(", "));
+                return Ok(ExitCode::from(EXIT_USAGE));
+            }
+        }
+    }
+    if let Some(lang) = &cli.lang {
+        config.ocr_language = lang.clone();
+    }
+    if cli.no_preprocess {
+        config.preprocess.steps.clear();
+    }
+
+    // 只识别图片文件时不需要截图来源
+    let screen_source: Arc<dyn ScreenSource> = if cli.screen || cli.region.is_some() {
+        screenshot::source_from_env()?
+    } else {
+        Arc::new(SystemScreenSource)
+    };
+    let pipeline = OcrPipeline::new(screen_source, registry);
+
+    let mut failed = false;
+    let mut stdout = std::io::stdout().lock();
+
+    if cli.format == OutputFormat::Tsv {
+        writeln!(stdout, "source\tblock\tline\tword\tleft\ttop\twidth\theight\tconfidence\ttext")?;
+    }
+
+    for input in collect_inputs(&cli, &pipeline).await {
+        let result = match input {
+            Ok(input) => recognize(&input, &pipeline, &config)
+                .await
+                .map(|result| (input, result)),
+            Err(e) => Err(e),
+        };
+
+        match result {
+            Ok((input, result)) => write_result(&mut stdout, cli.format, &input, &result)?,
+            Err(e) => {
+                eprintln!("❌ {:#}", e);
+                failed = true;
+            }
+        }
+    }
+
+    stdout.flush()?;
+    Ok(if failed {
+        ExitCode::from(EXIT_FAILURE)
+    } else {
+        ExitCode::SUCCESS
+    })
+}
+
+/// 读取配置文件，失败时使用默认配置
+fn load_config() -> AppConfig {
+    let path = match AppConfig::get_config_path() {
+        Ok(path) if path.exists() => path,
+        _ => return AppConfig::default(),
+    };
+
+    AppConfig::read_from(&path).unwrap_or_else(|e| {
+        eprintln!("⚠️  加载配置失败，使用默认配置: {:#}", e);
+        AppConfig::default()
+    })
+}
+
+/// 按命令行参数收集输入
+async fn collect_inputs(cli: &Cli, pipeline: &OcrPipeline) -> Vec<Result<Input>> {
+    let mut inputs = Vec::new();
+
+    if cli.screen || cli.region.is_some() {
+        inputs.push(capture_screen(pipeline, cli.region).await);
+    }
+
+    for name in &cli.inputs {
+        inputs.push(read_image(name).map(|capture| Input {
+            name: name.clone(),
+            capture,
+        }));
+    }
+
+    inputs
+}
+
+async fn capture_screen(pipeline: &OcrPipeline, region: Option<ScreenRegion>) -> Result<Input> {
+    let (name, source) = match region {
+        Some(region) => (
+            format!("region:{},{},{}x{}", region.x, region.y, region.width, region.height),
+            Source::Region(region),
+        ),
+        None => ("screen".to_string(), Source::PrimaryScreen),
+    };
+    let capture = pipeline.capture(source).await?;
+    Ok(Input { name, capture })
+}
+
+fn read_image(name: &str) -> Result<ScreenCapture> {
+    let img = if name == "-" {
+        let mut bytes = Vec::new();
+        std::io::stdin()
+            .read_to_end(&mut bytes)
+            .context("读取标准输入失败")?;
+        image::load_from_memory(&bytes).context("无法解析标准输入中的图片")?
+    } else {
+        image::open(name).with_context(|| format!("无法打开图片: {}", name))?
+    }
+    .to_rgba8();
+
+    Ok(ScreenCapture::from_image(img, 0, 0))
+}
+
+async fn recognize(input: &Input, pipeline: &OcrPipeline, config: &AppConfig) -> Result<OcrResult> {
+    pipeline
+        .recognize(&input.capture, config)
+        .await
+        .with_context(|| input.name.clone())
+}
+
+fn write_result(out: &mut impl Write, format: OutputFormat, input: &Input, result: &OcrResult) -> Result<()> {
+    match format {
+        OutputFormat::Text => {
+            writeln!(out, "{}", result.text.trim_end())?;
+        }
+        OutputFormat::Json => {
+            writeln!(out, "{}", serde_json::to_string(result)?)?;
+        }
+        OutputFormat::Tsv => {
+            for (b, block) in result.blocks.iter().enumerate() {
+                for (l, line) in block.lines.iter().enumerate() {
+                    for (w, word) in line.words.iter().enumerate() {
+                        writeln!(
+                            out,
+                            "{}\t{}\t{}\t{}\t{}\t{}\t{}\t{}\t{:.3}\t{}",
+                            input.name,
+                            b + 1,
+                            l + 1,
+                            w + 1,
+                            input.capture.x + word.bbox.x as i32,
+                            input.capture.y + word.bbox.y as i32,
+                            word.bbox.width,
+                            word.bbox.height,
+                            word.confidence,
+                            word.text.replace(['\t', '\n'], " ")
+                        )?;
+                    }
+                }
+            }
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_region() {
+        let region = parse_region("-1920,10,800x600").unwrap();
+        assert_eq!((region.x, region.y, region.width, region.height), (-1920, 10, 800, 600));
+
+        assert!(parse_region("10,10").is_err());
+        assert!(parse_region("10,10,0x5").is_err());
+    }
+
+    #[test]
+    fn test_cli_requires_region_or_screen_exclusive() {
+        assert!(Cli::try_parse_from(["screenocr-cli", "--screen", "--region", "0,0,10x10"]).is_err());
+
+        let cli = Cli::try_parse_from(["screenocr-cli", "--region", "-100,0,10x10", "-f", "tsv"]).unwrap();
+        assert_eq!(cli.format, OutputFormat::Tsv);
+        assert_eq!(cli.region.unwrap().x, -100);
+    }
+}
@@ -1,74 +1,869 @@
 use serde::{Deserialize, Serialize};
-use std::fs;
-use std::path::PathBuf;
-use anyhow::{Context, Result};
+use serde_json::{Map, Value};
+use std::collections::{BTreeMap, HashMap};
+use std::ffi::OsString;
+use std::fmt;
+use std::fs::{self, File};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::sync::OnceLock;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use anyhow::{anyhow, Context, Result};
+use thiserror::Error;
+use crate::preprocess::PreprocessConfig;
+use crate::window::{wildcard_match, ActiveWindowProvider, WindowInfo};
 
-#[derive(Debug, Serialize, Deserialize, Clone)]
+pub mod watcher;
+
+/// 当前的配置文件版本，没有 `version` 字段的旧文件视为版本 1
+pub const CONFIG_VERSION: u32 = 2;
+
+/// 触发延时上限
+pub const MAX_TRIGGER_DELAY_MS: u32 = 5000;
+/// 按键序列超时的范围
+pub const SEQUENCE_TIMEOUT_RANGE_MS: (u32, u32) = (100, 5000);
+/// 历史记录保留条数上限
+pub const MAX_HISTORY_LIMIT: usize = 100_000;
+/// 未单独设置超时的引擎使用的识别超时
+pub const DEFAULT_OCR_TIMEOUT_MS: u32 = 30_000;
+/// 识别超时的范围
+pub const OCR_TIMEOUT_RANGE_MS: (u32, u32) = (1000, 600_000);
+
+/// 保存时保留的历史配置份数（config.json.bak.1 最新）
+pub const CONFIG_BACKUP_COUNT: usize = 3;
+
+/// 指定配置文件路径的环境变量，优先级低于命令行参数 `--config`
+pub const CONFIG_PATH_ENV: &str = "SCREENOCR_CONFIG";
+/// 程序所在目录中有该文件时进入便携模式，配置和历史保存在程序目录的 `data` 子目录
+pub const PORTABLE_MARKER: &str = "portable";
+
+/// 命令行参数指定的配置文件路径，启动时设置一次
+static CONFIG_PATH_OVERRIDE: OnceLock<PathBuf> = OnceLock::new();
+
+/// 配置升级步骤，`MIGRATIONS[i]` 把版本 `i + 1` 升级到 `i + 2`
+const MIGRATIONS: &[fn(&mut Map<String, Value>)] = &[migrate_v1_to_v2];
+
+/// 缺失的字段使用默认值，旧版本的配置文件也能直接读取
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+#[serde(default)]
 pub struct AppConfig {
-    pub ocr_engine: String,
+    /// 配置文件版本
+    pub version: u32,
+    pub ocr_engine: OcrEngine,
+    /// OCR 语言包（Tesseract 格式，例如 "chi_sim+eng"）
+    pub ocr_language: String,
+    /// PaddleOCR 模型目录，为空时使用默认数据目录
+    pub onnx_model_dir: Option<String>,
+    /// 识别前的图像预处理
+    pub preprocess: PreprocessConfig,
+    /// 是否记录识别历史
+    pub history_enabled: bool,
+    /// 历史记录保留条数（不含置顶），0 表示不限制
+    pub history_limit: usize,
+    /// 历史记录是否保存缩略图
+    pub history_thumbnails: bool,
     pub trigger_delay_ms: u32,
+    /// 按键序列（如 "Ctrl+K Ctrl+O"、"Shift Shift"）相邻两步的最大间隔
+    pub sequence_timeout_ms: u32,
+    /// 全屏识别热键
     pub hotkey: String,
+    /// 其他热键与动作的绑定
+    pub hotkey_bindings: Vec<HotkeyBindingConfig>,
     pub auto_copy: bool,
+    /// 命名的识别设置方案
+    pub profiles: Vec<Profile>,
+    /// 当前方案，修改引擎、语言和预处理设置时同步保存到该方案
+    pub active_profile: Option<String>,
+    /// 按前台窗口自动选择方案的规则，按顺序使用第一条匹配的规则
+    pub profile_rules: Vec<ProfileRule>,
+    /// 各引擎的识别超时，未设置的引擎使用 `DEFAULT_OCR_TIMEOUT_MS`
+    pub ocr_timeouts_ms: BTreeMap<OcrEngine, u32>,
+    /// 上一次识别还没完成时再次触发的处理方式
+    pub retrigger_policy: RetriggerPolicy,
+}
+
+/// 识别进行中再次触发（热键、托盘或前端命令）时的处理方式
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RetriggerPolicy {
+    /// 忽略新的触发
+    #[default]
+    Ignore,
+    /// 取消进行中的识别，执行新的识别
+    CancelPrevious,
+    /// 排队，等前面的识别完成后再执行
+    Queue,
+}
+
+/// 前台窗口匹配时，本次识别使用指定方案的设置（不改变当前方案）
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ProfileRule {
+    /// 窗口类名通配符（不区分大小写，例如 "*terminal*"），省略时不限制
+    #[serde(default)]
+    pub window_class: Option<String>,
+    /// 窗口标题通配符（例如 "*.pdf - *"），省略时不限制
+    #[serde(default)]
+    pub window_title: Option<String>,
+    /// 方案名称
+    pub profile: String,
+}
+
+impl ProfileRule {
+    pub fn matches(&self, window: &WindowInfo) -> bool {
+        let class = self.window_class.as_deref().is_none_or(|pattern| wildcard_match(pattern, &window.class));
+        let title = self.window_title.as_deref().is_none_or(|pattern| wildcard_match(pattern, &window.title));
+        class && title
+    }
+}
+
+/// 识别设置方案（例如 "中文文档"、"英文代码"），切换时覆盖当前的引擎、语言和预处理设置
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Profile {
+    pub name: String,
+    pub ocr_engine: OcrEngine,
+    pub ocr_language: String,
+    pub preprocess: PreprocessConfig,
+    /// 切换到该方案的热键
+    pub hotkey: Option<String>,
+}
+
+impl Default for Profile {
+    fn default() -> Self {
+        Self {
+            name: String::new(),
+            ocr_engine: OcrEngine::default(),
+            ocr_language: default_ocr_language(),
+            preprocess: PreprocessConfig::default(),
+            hotkey: None,
+        }
+    }
+}
+
+/// 内置的 OCR 引擎，序列化名称与 `OcrBackend::name` 一致
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Default, Serialize, Deserialize)]
+pub enum OcrEngine {
+    #[default]
+    Tesseract,
+    #[serde(rename = "WeChatOCR")]
+    WeChatOcr,
+    #[serde(rename = "PaddleOCR")]
+    PaddleOcr,
+    /// 读取到的未知引擎名称（例如降级后或其他构建写入的引擎），校验时报错，读取文件时恢复为默认引擎
+    #[serde(other)]
+    Unknown,
+}
+
+impl OcrEngine {
+    pub const ALL: [OcrEngine; 3] = [OcrEngine::Tesseract, OcrEngine::WeChatOcr, OcrEngine::PaddleOcr];
+
+    /// 引擎名称，用于在 `OcrRegistry` 中查找
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Tesseract => "Tesseract",
+            Self::WeChatOcr => "WeChatOCR",
+            Self::PaddleOcr => "PaddleOCR",
+            Self::Unknown => "Unknown",
+        }
+    }
+
+    /// 不区分大小写地解析引擎名称
+    pub fn parse(name: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|engine| engine.as_str().eq_ignore_ascii_case(name.trim()))
+    }
+}
+
+impl fmt::Display for OcrEngine {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// 单个字段的校验错误，`field` 为 JSON 路径（例如 "hotkey_bindings[1].hotkey"）
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct FieldError {
+    pub field: String,
+    pub message: String,
+}
+
+impl FieldError {
+    pub fn new(field: impl Into<String>, message: impl Into<String>) -> Self {
+        Self {
+            field: field.into(),
+            message: message.into(),
+        }
+    }
+}
+
+impl fmt::Display for FieldError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}: {}", self.field, self.message)
+    }
+}
+
+/// 更新配置失败
+#[derive(Debug, Error)]
+pub enum ConfigError {
+    /// 一个或多个字段无效
+    #[error("配置无效: {}", .0.iter().map(|e| e.to_string()).collect::<Vec<_>>().join("; "))]
+    Invalid(Vec<FieldError>),
+    /// 配置文件无法解析
+    #[error("{0:#}")]
+    Parse(anyhow::Error),
+    /// 写入配置文件失败
+    #[error("保存配置失败: {0:#}")]
+    Save(anyhow::Error),
+    /// 没有该名称的配置方案
+    #[error("配置方案 \"{0}\" 不存在")]
+    UnknownProfile(String),
+}
+
+impl ConfigError {
+    pub fn kind(&self) -> &'static str {
+        match self {
+            Self::Invalid(_) => "invalid",
+            Self::Parse(_) => "parse",
+            Self::Save(_) => "save",
+            Self::UnknownProfile(_) => "unknown_profile",
+        }
+    }
+}
+
+/// 热键可以触发的动作
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum HotkeyAction {
+    /// 全屏识别
+    FullScreenOcr,
+    /// 打开区域选择覆盖层
+    RegionSelect,
+    /// 重新识别上一次选择的区域
+    RepeatLastRegion,
+    /// 识别剪贴板中的图片
+    OcrClipboardImage,
+    /// 显示识别历史
+    ShowHistory,
+    /// 开关实时识别（持续识别上一次选择的区域）
+    ToggleLiveMode,
+    /// 切换到 `profiles` 中指定序号的方案
+    ActivateProfile(usize),
+}
+
+/// 导入配置的方式
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ImportMode {
+    /// 只覆盖导入文件中出现的配置项，同名方案被替换，其余方案保留
+    Merge,
+    /// 整体替换当前配置，缺失的配置项使用默认值
+    Replace,
+}
+
+/// 单个热键绑定
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct HotkeyBindingConfig {
+    pub hotkey: String,
+    pub action: HotkeyAction,
+}
+
+fn default_ocr_language() -> String {
+    "chi_sim+eng".to_string()
+}
+
+fn default_history_limit() -> usize {
+    500
+}
+
+fn default_sequence_timeout_ms() -> u32 {
+    800
 }
 
 impl Default for AppConfig {
     fn default() -> Self {
         Self {
-            ocr_engine: "Tesseract".to_string(),
+            version: CONFIG_VERSION,
+            ocr_engine: OcrEngine::Tesseract,
+            ocr_language: default_ocr_language(),
+            onnx_model_dir: None,
+            preprocess: PreprocessConfig::default(),
+            history_enabled: true,
+            history_limit: default_history_limit(),
+            history_thumbnails: true,
             trigger_delay_ms: 300,
+            sequence_timeout_ms: default_sequence_timeout_ms(),
             hotkey: "Alt".to_string(),
+            hotkey_bindings: Vec::new(),
             auto_copy: true,
+            profiles: Vec::new(),
+            active_profile: None,
+            profile_rules: Vec::new(),
+            ocr_timeouts_ms: BTreeMap::new(),
+            retrigger_policy: RetriggerPolicy::default(),
         }
     }
 }
 
 impl AppConfig {
+    /// 所有热键绑定，`hotkey` 作为全屏识别排在最前（为空时表示未设置）
+    ///
+    /// 设置了热键的方案排在最后。
+    pub fn all_hotkey_bindings(&self) -> Vec<HotkeyBindingConfig> {
+        let primary = (!self.hotkey.trim().is_empty()).then(|| HotkeyBindingConfig {
+            hotkey: self.hotkey.clone(),
+            action: HotkeyAction::FullScreenOcr,
+        });
+        let profiles = self.profiles.iter().enumerate().filter_map(|(i, profile)| {
+            profile.hotkey.as_ref().map(|hotkey| HotkeyBindingConfig {
+                hotkey: hotkey.clone(),
+                action: HotkeyAction::ActivateProfile(i),
+            })
+        });
+        primary
+            .into_iter()
+            .chain(self.hotkey_bindings.iter().cloned())
+            .chain(profiles)
+            .collect()
+    }
+    
+    /// `hotkey_bindings` 第 `index` 项在 `all_hotkey_bindings` 中的位置，`index` 为 None 时表示主热键 `hotkey`
+    ///
+    /// 该项不存在（例如新增绑定、主热键未设置）时返回 None。
+    pub fn hotkey_binding_position(&self, index: Option<usize>) -> Option<usize> {
+        let primary = usize::from(!self.hotkey.trim().is_empty());
+        match index {
+            None => (primary == 1).then_some(0),
+            Some(index) => (index < self.hotkey_bindings.len()).then_some(primary + index),
+        }
+    }
+    
+    /// 引擎的识别超时
+    pub fn ocr_timeout(&self, engine: OcrEngine) -> Duration {
+        let ms = self.ocr_timeouts_ms.get(&engine).copied().unwrap_or(DEFAULT_OCR_TIMEOUT_MS);
+        Duration::from_millis(ms as u64)
+    }
+    
+    /// 当前方案
+    pub fn active_profile(&self) -> Option<&Profile> {
+        let name = self.active_profile.as_deref()?;
+        self.profiles.iter().find(|profile| profile.name == name)
+    }
+    
+    /// 第一条匹配前台窗口的规则对应的方案
+    pub fn profile_for_window(&self, window: &WindowInfo) -> Option<&Profile> {
+        let rule = self.profile_rules.iter().find(|rule| rule.matches(window))?;
+        self.profiles.iter().find(|profile| profile.name == rule.profile)
+    }
+    
+    /// 选择方案依据的窗口：优先使用热键按下时记录的窗口，没有记录时查询当前前台窗口
+    ///
+    /// 没有 profile_rules 时不需要窗口，也不查询。
+    pub fn trigger_window(&self, recorded: Option<WindowInfo>, provider: &dyn ActiveWindowProvider) -> Option<WindowInfo> {
+        if self.profile_rules.is_empty() {
+            return None;
+        }
+        recorded.or_else(|| {
+            provider.active_window().unwrap_or_else(|e| {
+                eprintln!("⚠️  查询前台窗口失败: {}", e);
+                None
+            })
+        })
+    }
+    
+    /// 本次识别使用的配置：有规则匹配前台窗口时使用对应方案的引擎、语言和预处理设置
+    pub fn for_window(&self, window: Option<&WindowInfo>) -> AppConfig {
+        let mut config = self.clone();
+        if let Some(profile) = window.and_then(|window| self.profile_for_window(window)) {
+            config.apply_profile_settings(profile);
+        }
+        config
+    }
+    
+    /// 用当前的引擎、语言和预处理设置创建方案，并设为当前方案
+    pub fn create_profile(&mut self, name: &str) -> Result<(), ConfigError> {
+        let name = self.new_profile_name(name)?;
+        self.profiles.push(Profile {
+            name: name.clone(),
+            ocr_engine: self.ocr_engine,
+            ocr_language: self.ocr_language.clone(),
+            preprocess: self.preprocess.clone(),
+            hotkey: None,
+        });
+        self.active_profile = Some(name);
+        Ok(())
+    }
+    
+    pub fn rename_profile(&mut self, name: &str, new_name: &str) -> Result<(), ConfigError> {
+        let index = self.profile_index(name)?;
+        let new_name = self.new_profile_name(new_name)?;
+        if self.active_profile.as_deref() == Some(name) {
+            self.active_profile = Some(new_name.clone());
+        }
+        for rule in self.profile_rules.iter_mut().filter(|rule| rule.profile == name) {
+            rule.profile = new_name.clone();
+        }
+        self.profiles[index].name = new_name;
+        Ok(())
+    }
+    
+    /// 删除方案及指向它的规则，删除当前方案时保留当前设置
+    ///
+    /// 后面方案的序号会前移，`hotkey_bindings` 中指向它们的 `activate_profile` 随之调整。
+    pub fn delete_profile(&mut self, name: &str) -> Result<(), ConfigError> {
+        let index = self.profile_index(name)?;
+        self.profiles.remove(index);
+        if self.active_profile.as_deref() == Some(name) {
+            self.active_profile = None;
+        }
+        self.profile_rules.retain(|rule| rule.profile != name);
+        self.hotkey_bindings.retain(|b| b.action != HotkeyAction::ActivateProfile(index));
+        for binding in &mut self.hotkey_bindings {
+            if let HotkeyAction::ActivateProfile(i) = &mut binding.action {
+                if *i > index {
+                    *i -= 1;
+                }
+            }
+        }
+        Ok(())
+    }
+    
+    /// 复制方案，不复制热键
+    pub fn duplicate_profile(&mut self, name: &str, new_name: &str) -> Result<(), ConfigError> {
+        let index = self.profile_index(name)?;
+        let new_name = self.new_profile_name(new_name)?;
+        let profile = Profile {
+            name: new_name,
+            hotkey: None,
+            ..self.profiles[index].clone()
+        };
+        self.profiles.insert(index + 1, profile);
+        for binding in &mut self.hotkey_bindings {
+            if let HotkeyAction::ActivateProfile(i) = &mut binding.action {
+                if *i > index {
+                    *i += 1;
+                }
+            }
+        }
+        Ok(())
+    }
+    
+    /// 切换到方案，用它覆盖当前的引擎、语言和预处理设置
+    pub fn activate_profile(&mut self, name: &str) -> Result<(), ConfigError> {
+        let profile = self.profiles[self.profile_index(name)?].clone();
+        self.apply_profile_settings(&profile);
+        self.active_profile = Some(profile.name);
+        Ok(())
+    }
+    
+    /// 使用方案的引擎、语言和预处理设置（不改变当前方案）
+    pub fn apply_profile_settings(&mut self, profile: &Profile) {
+        self.ocr_engine = profile.ocr_engine;
+        self.ocr_language = profile.ocr_language.clone();
+        self.preprocess = profile.preprocess.clone();
+    }
+    
+    /// 把当前的引擎、语言和预处理设置写回当前方案
+    pub fn sync_active_profile(&mut self) {
+        let Some(name) = self.active_profile.clone() else { return };
+        if let Some(profile) = self.profiles.iter_mut().find(|profile| profile.name == name) {
+            profile.ocr_engine = self.ocr_engine;
+            profile.ocr_language = self.ocr_language.clone();
+            profile.preprocess = self.preprocess.clone();
+        }
+    }
+    
+    fn profile_index(&self, name: &str) -> Result<usize, ConfigError> {
+        self.profiles
+            .iter()
+            .position(|profile| profile.name == name)
+            .ok_or_else(|| ConfigError::UnknownProfile(name.to_string()))
+    }
+    
+    /// 检查新方案名称，返回去掉首尾空白的名称
+    fn new_profile_name(&self, name: &str) -> Result<String, ConfigError> {
+        let name = name.trim();
+        let message = if name.is_empty() {
+            "方案名称不能为空"
+        } else if self.profiles.iter().any(|profile| profile.name == name) {
+            "已有同名方案"
+        } else {
+            return Ok(name.to_string());
+        };
+        Err(ConfigError::Invalid(vec![FieldError::new("name", message)]))
+    }
+    
+    /// 校验取值范围，返回全部无效字段
+    pub fn validate(&self) -> Result<(), ConfigError> {
+        let errors = self.field_errors();
+        if errors.is_empty() {
+            Ok(())
+        } else {
+            Err(ConfigError::Invalid(errors))
+        }
+    }
+
+    fn field_errors(&self) -> Vec<FieldError> {
+        let mut errors = Vec::new();
+
+        if self.ocr_engine == OcrEngine::Unknown {
+            errors.push(FieldError::new("ocr_engine", ENGINE_HINT));
+        }
+        if !is_valid_language(&self.ocr_language) {
+            errors.push(FieldError::new("ocr_language", LANGUAGE_HINT));
+        }
+        if self.onnx_model_dir.as_deref().is_some_and(|dir| dir.trim().is_empty()) {
+            errors.push(FieldError::new("onnx_model_dir", "不能为空字符串，使用默认目录时请省略"));
+        }
+        if self.history_limit > MAX_HISTORY_LIMIT {
+            errors.push(FieldError::new("history_limit", format!("不能超过 {}", MAX_HISTORY_LIMIT)));
+        }
+        if self.trigger_delay_ms > MAX_TRIGGER_DELAY_MS {
+            errors.push(FieldError::new("trigger_delay_ms", format!("应在 0 到 {} 之间", MAX_TRIGGER_DELAY_MS)));
+        }
+        let (min, max) = SEQUENCE_TIMEOUT_RANGE_MS;
+        if !(min..=max).contains(&self.sequence_timeout_ms) {
+            errors.push(FieldError::new("sequence_timeout_ms", format!("应在 {} 到 {} 之间", min, max)));
+        }
+        for (i, binding) in self.hotkey_bindings.iter().enumerate() {
+            if binding.hotkey.trim().is_empty() {
+                errors.push(FieldError::new(format!("hotkey_bindings[{}].hotkey", i), "热键不能为空"));
+            }
+            if let HotkeyAction::ActivateProfile(index) = binding.action {
+                if index >= self.profiles.len() {
+                    errors.push(FieldError::new(format!("hotkey_bindings[{}].action", i), "方案序号超出范围"));
+                }
+            }
+        }
+        let (min, max) = OCR_TIMEOUT_RANGE_MS;
+        for (engine, timeout) in &self.ocr_timeouts_ms {
+            if *engine == OcrEngine::Unknown {
+                errors.push(FieldError::new(format!("ocr_timeouts_ms.{}", engine), ENGINE_HINT));
+            } else if !(min..=max).contains(timeout) {
+                errors.push(FieldError::new(format!("ocr_timeouts_ms.{}", engine), format!("应在 {} 到 {} 之间", min, max)));
+            }
+        }
+        preprocess_errors("preprocess", &self.preprocess, &mut errors);
+        
+        for (i, profile) in self.profiles.iter().enumerate() {
+            let field = |name: &str| format!("profiles[{}].{}", i, name);
+            if profile.name.trim().is_empty() {
+                errors.push(FieldError::new(field("name"), "方案名称不能为空"));
+            } else if self.profiles[..i].iter().any(|other| other.name == profile.name) {
+                errors.push(FieldError::new(field("name"), "已有同名方案"));
+            }
+            if profile.ocr_engine == OcrEngine::Unknown {
+                errors.push(FieldError::new(field("ocr_engine"), ENGINE_HINT));
+            }
+            if !is_valid_language(&profile.ocr_language) {
+                errors.push(FieldError::new(field("ocr_language"), LANGUAGE_HINT));
+            }
+            if profile.hotkey.as_deref().is_some_and(|hotkey| hotkey.trim().is_empty()) {
+                errors.push(FieldError::new(field("hotkey"), "不能为空字符串，不需要热键时请省略"));
+            }
+            preprocess_errors(&field("preprocess"), &profile.preprocess, &mut errors);
+        }
+        if self.active_profile.is_some() && self.active_profile().is_none() {
+            errors.push(FieldError::new("active_profile", "没有该名称的方案"));
+        }
+        for (i, rule) in self.profile_rules.iter().enumerate() {
+            if rule.window_class.is_none() && rule.window_title.is_none() {
+                errors.push(FieldError::new(format!("profile_rules[{}]", i), "至少需要 window_class 或 window_title"));
+            }
+            if !self.profiles.iter().any(|profile| profile.name == rule.profile) {
+                errors.push(FieldError::new(format!("profile_rules[{}].profile", i), "没有该名称的方案"));
+            }
+        }
+        errors
+    }
+
+    /// 把无效字段恢复为默认值，用于读取手动编辑过的配置文件
+    fn repair(&mut self) {
+        let default = Self::default();
+        for error in self.field_errors() {
+            eprintln!("⚠️  配置项 {} 无效（{}），使用默认值", error.field, error.message);
+            match error.field.split(['.', '[']).next().unwrap_or_default() {
+                "ocr_engine" => self.ocr_engine = default.ocr_engine,
+                "ocr_language" => self.ocr_language = default.ocr_language.clone(),
+                "onnx_model_dir" => self.onnx_model_dir = None,
+                "history_limit" => self.history_limit = default.history_limit,
+                "trigger_delay_ms" => self.trigger_delay_ms = default.trigger_delay_ms,
+                "sequence_timeout_ms" => self.sequence_timeout_ms = default.sequence_timeout_ms,
+                "ocr_timeouts_ms" => {
+                    let (min, max) = OCR_TIMEOUT_RANGE_MS;
+                    self.ocr_timeouts_ms
+                        .retain(|engine, timeout| *engine != OcrEngine::Unknown && (min..=max).contains(timeout));
+                }
+                "hotkey_bindings" => {
+                    let profiles = self.profiles.len();
+                    self.hotkey_bindings.retain(|b| {
+                        !b.hotkey.trim().is_empty()
+                            && !matches!(b.action, HotkeyAction::ActivateProfile(i) if i >= profiles)
+                    });
+                }
+                "preprocess" => self.preprocess = default.preprocess.clone(),
+                "profiles" => self.repair_profile(&error.field),
+                "active_profile" => self.active_profile = None,
+                "profile_rules" => {
+                    let profiles = &self.profiles;
+                    self.profile_rules.retain(|rule| {
+                        (rule.window_class.is_some() || rule.window_title.is_some())
+                            && profiles.iter().any(|profile| profile.name == rule.profile)
+                    });
+                }
+                _ => {}
+            }
+        }
+    }
+    
+    /// 把方案中的无效字段恢复为默认值，名称无效的方案自动改名
+    fn repair_profile(&mut self, field: &str) {
+        let Some((index, name)) = field
+            .strip_prefix("profiles[")
+            .and_then(|rest| rest.split_once("]."))
+            .and_then(|(index, name)| Some((index.parse::<usize>().ok()?, name)))
+        else {
+            return;
+        };
+        
+        let default = Profile::default();
+        let taken: Vec<String> = self.profiles.iter().map(|profile| profile.name.clone()).collect();
+        let profile = &mut self.profiles[index];
+        match name.split('.').next().unwrap_or_default() {
+            "name" => {
+                profile.name = (index + 1..)
+                    .map(|n| format!("方案 {}", n))
+                    .find(|candidate| !taken.contains(candidate))
+                    .unwrap_or_default();
+            }
+            "ocr_engine" => profile.ocr_engine = default.ocr_engine,
+            "ocr_language" => profile.ocr_language = default.ocr_language,
+            "hotkey" => profile.hotkey = None,
+            "preprocess" => profile.preprocess = default.preprocess,
+            _ => {}
+        }
+    }
+
+    /// 使用指定的配置文件（命令行参数 `--config`），需要在第一次读取配置之前调用
+    pub fn set_config_path(path: &Path) -> Result<()> {
+        let path = std::path::absolute(path)
+            .with_context(|| format!("配置文件路径无效: {:?}", path))?;
+        CONFIG_PATH_OVERRIDE
+            .set(path)
+            .map_err(|_| anyhow!("配置文件路径已经设置"))
+    }
+    
     /// 获取配置文件路径
+    ///
+    /// 依次使用 `--config` 参数、`SCREENOCR_CONFIG` 环境变量、便携模式下程序目录中的
+    /// `data/config.json`，都没有时使用系统配置目录。
     pub fn get_config_path() -> Result<PathBuf> {
-        let config_dir = dirs::config_dir()
-            .context("无法获取配置目录")?
-            .join("screenocr-tauri");
+        let exe_dir = std::env::current_exe()
+            .ok()
+            .and_then(|exe| exe.parent().map(Path::to_path_buf));
+        let path = resolve_config_path(
+            CONFIG_PATH_OVERRIDE.get().map(PathBuf::as_path),
+            std::env::var_os(CONFIG_PATH_ENV),
+            exe_dir.as_deref(),
+        )?;
         
         // 确保配置目录存在
-        fs::create_dir_all(&config_dir)
+        let config_dir = path.parent().context("配置文件路径无效")?;
+        fs::create_dir_all(config_dir)
             .context("创建配置目录失败")?;
         
-        Ok(config_dir.join("config.json"))
+        Ok(path)
     }
     
     /// 从文件加载配置
     pub fn load() -> Result<Self> {
         let config_path = Self::get_config_path()?;
-        
-        if !config_path.exists() {
+        Self::load_from(&config_path)
+    }
+    
+    /// 从指定文件加载配置
+    ///
+    /// 文件无法解析时改名保留原文件，然后依次尝试保存时留下的备份，都不可用时使用默认配置。
+    pub fn load_from(path: &Path) -> Result<Self> {
+        if !path.exists() {
             println!("📝 配置文件不存在，使用默认配置");
             return Ok(Self::default());
         }
         
-        let content = fs::read_to_string(&config_path)
+        let error = match Self::read_from(path) {
+            Ok(config) => {
+                println!("✅ 配置已从文件加载: {:?}", path);
+                return Ok(config);
+            }
+            Err(e) => e,
+        };
+        
+        let broken = backup_unreadable(path)?;
+        eprintln!("⚠️  {:#}，原文件已备份到 {:?}", error, broken);
+        
+        for n in 1..=CONFIG_BACKUP_COUNT {
+            let backup = backup_path(path, n);
+            if !backup.exists() {
+                continue;
+            }
+            match Self::read_from(&backup) {
+                Ok(config) => {
+                    println!("♻️  已从备份恢复配置: {:?}", backup);
+                    config.save_to(path)?;
+                    return Ok(config);
+                }
+                Err(e) => eprintln!("⚠️  备份 {:?} 也无法读取: {:#}", backup, e),
+            }
+        }
+        
+        eprintln!("⚠️  没有可用的配置备份，使用默认配置");
+        Ok(Self::default())
+    }
+    
+    /// 从指定文件读取配置，旧版本会先升级，无效字段恢复为默认值
+    pub fn read_from(path: &Path) -> Result<Self> {
+        let content = fs::read_to_string(path)
             .context("读取配置文件失败")?;
         
-        let config: Self = serde_json::from_str(&content)
+        Self::from_json(&content)
+    }
+    
+    /// 解析配置 JSON，旧版本会先升级，无效字段恢复为默认值
+    pub fn from_json(content: &str) -> Result<Self> {
+        let mut config = Self::parse(content)?;
+        config.repair();
+        Ok(config)
+    }
+    
+    /// 解析配置 JSON，旧版本会先升级，有无效字段时返回错误而不是恢复默认值
+    pub fn from_json_strict(content: &str) -> Result<Self, ConfigError> {
+        let config = Self::parse(content).map_err(ConfigError::Parse)?;
+        config.validate()?;
+        Ok(config)
+    }
+    
+    fn parse(content: &str) -> Result<Self> {
+        let mut value: Value = serde_json::from_str(content)
             .context("解析配置文件失败")?;
+        migrate(&mut value)?;
         
-        println!("✅ 配置已从文件加载: {:?}", config_path);
-        Ok(config)
+        serde_json::from_value(value)
+            .context("解析配置文件失败")
     }
     
     /// 保存配置到文件
     pub fn save(&self) -> Result<()> {
         let config_path = Self::get_config_path()?;
+        self.save_to(&config_path)
+    }
+    
+    /// 保存配置到指定文件
+    ///
+    /// 先写入临时文件并刷新到磁盘，再替换原文件，写入中途崩溃或磁盘已满都不会损坏原文件。
+    /// 原文件可以正常读取时轮换保留为 `.bak.1` 到 `.bak.N`。
+    pub fn save_to(&self, path: &Path) -> Result<()> {
+        let current = fs::read_to_string(path).ok();
+        // 新版本程序写的配置里可能有本程序不认识的配置项，覆盖保存会把它们丢掉
+        let file_version = current.as_deref().and_then(file_version).unwrap_or(0);
+        let newest = self.version.max(file_version);
+        if newest > CONFIG_VERSION {
+            anyhow::bail!(
+                "配置文件版本 {} 比当前程序支持的版本 {} 新，为避免丢失新版本的配置项，不会覆盖保存",
+                newest, CONFIG_VERSION
+            );
+        }
         
         let content = serde_json::to_string_pretty(self)
             .context("序列化配置失败")?;
         
-        fs::write(&config_path, content)
+        if current.is_some_and(|current| Self::from_json(&current).is_ok()) {
+            rotate_backups(path)?;
+        }
+        write_atomic(path, content.as_bytes())
             .context("写入配置文件失败")?;
         
-        println!("💾 配置已保存到文件: {:?}", config_path);
+        println!("💾 配置已保存到文件: {:?}", path);
         Ok(())
     }
     
+    /// 导出配置，供其他电脑导入
+    pub fn export_to(&self, path: &Path) -> Result<()> {
+        let content = serde_json::to_string_pretty(self)
+            .context("序列化配置失败")?;
+        write_atomic(path, content.as_bytes())
+            .with_context(|| format!("写入导出文件失败: {:?}", path))?;
+        
+        println!("📤 配置已导出到: {:?}", path);
+        Ok(())
+    }
+    
+    /// 在当前配置上导入配置 JSON，返回导入后的配置，有无效字段时返回错误
+    ///
+    /// 合并时对象类型的配置项（如 `preprocess`）逐项覆盖，列表整体替换；方案按名称合并，
+    /// 导入的热键绑定中的方案序号会换算为合并后的序号。
+    pub fn imported(&self, content: &str, mode: ImportMode) -> Result<AppConfig, ConfigError> {
+        if mode == ImportMode::Replace {
+            return Self::from_json_strict(content);
+        }
+        
+        let mut value: Value = serde_json::from_str(content)
+            .context("解析导入的配置失败")
+            .map_err(ConfigError::Parse)?;
+        migrate(&mut value).map_err(ConfigError::Parse)?;
+        let Value::Object(mut imported) = value else { unreachable!("migrate 已检查是 JSON 对象") };
+        let profiles = imported.remove("profiles");
+        let bindings = imported.remove("hotkey_bindings");
+        
+        let mut merged = serde_json::to_value(self)
+            .context("序列化配置失败")
+            .map_err(ConfigError::Parse)?;
+        merge_json(&mut merged, Value::Object(imported));
+        let mut config: AppConfig = serde_json::from_value(merged)
+            .context("解析导入的配置失败")
+            .map_err(ConfigError::Parse)?;
+        
+        // 导入文件中的方案序号 → 合并后的序号
+        let mut indices = HashMap::new();
+        let mut active_replaced = false;
+        if let Some(profiles) = profiles {
+            let profiles: Vec<Profile> = serde_json::from_value(profiles)
+                .context("解析导入的方案失败")
+                .map_err(ConfigError::Parse)?;
+            for (i, profile) in profiles.into_iter().enumerate() {
+                let index = match config.profiles.iter().position(|p| p.name == profile.name) {
+                    Some(index) => {
+                        active_replaced |= config.active_profile.as_deref() == Some(profile.name.as_str());
+                        config.profiles[index] = profile;
+                        index
+                    }
+                    None => {
+                        config.profiles.push(profile);
+                        config.profiles.len() - 1
+                    }
+                };
+                indices.insert(i, index);
+            }
+        }
+        if let Some(bindings) = bindings {
+            let mut bindings: Vec<HotkeyBindingConfig> = serde_json::from_value(bindings)
+                .context("解析导入的热键绑定失败")
+                .map_err(ConfigError::Parse)?;
+            for binding in &mut bindings {
+                if let HotkeyAction::ActivateProfile(i) = &mut binding.action {
+                    *i = indices.get(i).copied().unwrap_or(*i);
+                }
+            }
+            config.hotkey_bindings = bindings;
+        }
+        
+        // 当前方案被导入的同名方案替换时使用它的设置，否则把导入的设置保存到当前方案
+        match config.active_profile().cloned() {
+            Some(profile) if active_replaced => config.apply_profile_settings(&profile),
+            _ => config.sync_active_profile(),
+        }
+        
+        config.validate()?;
+        Ok(config)
+    }
+    
     /// 重置为默认配置
     pub fn reset() -> Result<Self> {
         let config = Self::default();
@@ -78,16 +873,191 @@ impl AppConfig {
     }
 }
 
+/// 按优先级选择配置文件路径，见 [`AppConfig::get_config_path`]
+fn resolve_config_path(arg: Option<&Path>, env: Option<OsString>, exe_dir: Option<&Path>) -> Result<PathBuf> {
+    if let Some(path) = arg {
+        return Ok(path.to_path_buf());
+    }
+    if let Some(path) = env.filter(|path| !path.is_empty()) {
+        return std::path::absolute(&path)
+            .with_context(|| format!("{} 指定的路径无效: {:?}", CONFIG_PATH_ENV, path));
+    }
+    if let Some(dir) = exe_dir.filter(|dir| dir.join(PORTABLE_MARKER).exists()) {
+        return Ok(dir.join("data").join("config.json"));
+    }
+    Ok(dirs::config_dir()
+        .context("无法获取配置目录")?
+        .join("screenocr-tauri")
+        .join("config.json"))
+}
+
+/// 把 `patch` 合并到 `target`：对象逐项合并，其他类型直接替换
+fn merge_json(target: &mut Value, patch: Value) {
+    match (target, patch) {
+        (Value::Object(target), Value::Object(patch)) => {
+            for (key, value) in patch {
+                match target.get_mut(&key) {
+                    Some(existing) => merge_json(existing, value),
+                    None => {
+                        target.insert(key, value);
+                    }
+                }
+            }
+        }
+        (target, patch) => *target = patch,
+    }
+}
+
+const LANGUAGE_HINT: &str = "应为用 + 连接的语言包名，例如 \"chi_sim+eng\"";
+
+const ENGINE_HINT: &str = "未知的 OCR 引擎，可选 Tesseract、WeChatOCR、PaddleOCR";
+
+fn is_valid_language(language: &str) -> bool {
+    !language.is_empty() && language.chars().all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '+')
+}
+
+fn preprocess_errors(field: &str, preprocess: &PreprocessConfig, errors: &mut Vec<FieldError>) {
+    if !(1..=8).contains(&preprocess.max_upscale) {
+        errors.push(FieldError::new(format!("{}.max_upscale", field), "应在 1 到 8 之间"));
+    }
+    if preprocess.binarize_window < 3 || preprocess.binarize_window.is_multiple_of(2) {
+        errors.push(FieldError::new(format!("{}.binarize_window", field), "应为不小于 3 的奇数"));
+    }
+    if !(0.0..=45.0).contains(&preprocess.max_skew_degrees) {
+        errors.push(FieldError::new(format!("{}.max_skew_degrees", field), "应在 0 到 45 之间"));
+    }
+}
+
+/// 读取配置文件中记录的版本号，无法解析时返回 `None`
+fn file_version(content: &str) -> Option<u32> {
+    let value: Value = serde_json::from_str(content).ok()?;
+    value.get("version")?.as_u64().and_then(|v| u32::try_from(v).ok())
+}
+
+/// 把配置 JSON 升级到当前版本
+fn migrate(value: &mut Value) -> Result<()> {
+    let map = value.as_object_mut().context("配置文件应为 JSON 对象")?;
+    let from = match map.get("version") {
+        None => 1,
+        Some(version) => version
+            .as_u64()
+            .and_then(|v| u32::try_from(v).ok())
+            .filter(|v| *v >= 1)
+            .context("配置文件版本无效")?,
+    };
+    
+    if from > CONFIG_VERSION {
+        eprintln!("⚠️  配置文件版本 {} 比当前程序支持的版本 {} 新，未知的配置项会被忽略，修改也不会保存", from, CONFIG_VERSION);
+        return Ok(());
+    }
+    
+    for (i, step) in MIGRATIONS.iter().enumerate().skip(from as usize - 1) {
+        step(map);
+        map.insert("version".to_string(), Value::from(i as u32 + 2));
+    }
+    if from < CONFIG_VERSION {
+        println!("🔄 配置文件已从版本 {} 升级到 {}", from, CONFIG_VERSION);
+    }
+    Ok(())
+}
+
+/// 版本 1 → 2：早期设置页面写入的 camelCase 字段改为 snake_case，引擎名称规范化
+fn migrate_v1_to_v2(map: &mut Map<String, Value>) {
+    for (old, new) in [
+        ("ocrEngine", "ocr_engine"),
+        ("triggerDelayMs", "trigger_delay_ms"),
+        ("autoCopy", "auto_copy"),
+    ] {
+        if let Some(value) = map.remove(old) {
+            map.entry(new).or_insert(value);
+        }
+    }
+    
+    if let Some(Value::String(name)) = map.get("ocr_engine") {
+        match OcrEngine::parse(name) {
+            Some(engine) => {
+                map.insert("ocr_engine".to_string(), Value::from(engine.as_str()));
+            }
+            None => {
+                eprintln!("⚠️  未知的 OCR 引擎 {:?}，使用默认引擎", name);
+                map.remove("ocr_engine");
+            }
+        }
+    }
+}
+
+/// 第 `n` 份备份的路径（从 1 开始，越小越新）
+fn backup_path(path: &Path, n: usize) -> PathBuf {
+    let mut name = path.file_name().unwrap_or_default().to_os_string();
+    name.push(format!(".bak.{}", n));
+    path.with_file_name(name)
+}
+
+/// 把当前配置复制为最新的备份，最旧的一份被丢弃
+fn rotate_backups(path: &Path) -> Result<()> {
+    for n in (1..CONFIG_BACKUP_COUNT).rev() {
+        let from = backup_path(path, n);
+        if from.exists() {
+            fs::rename(&from, backup_path(path, n + 1))
+                .with_context(|| format!("轮换配置备份失败: {:?}", from))?;
+        }
+    }
+    fs::copy(path, backup_path(path, 1))
+        .context("备份配置文件失败")?;
+    Ok(())
+}
+
+/// 写入同目录下的临时文件，fsync 后改名替换目标文件
+fn write_atomic(path: &Path, content: &[u8]) -> std::io::Result<()> {
+    let mut name = path.file_name().unwrap_or_default().to_os_string();
+    name.push(".tmp");
+    let temp = path.with_file_name(name);
+    
+    let written = File::create(&temp).and_then(|mut file| {
+        file.write_all(content)?;
+        file.sync_all()
+    });
+    if let Err(e) = written.and_then(|()| fs::rename(&temp, path)) {
+        let _ = fs::remove_file(&temp);
+        return Err(e);
+    }
+    
+    // 改名本身也需要落盘（Windows 上无法打开目录，忽略）
+    if let Some(dir) = path.parent() {
+        if let Ok(dir) = File::open(dir) {
+            let _ = dir.sync_all();
+        }
+    }
+    Ok(())
+}
+
+/// 把无法读取的配置文件改名保存，返回备份路径
+fn backup_unreadable(path: &Path) -> Result<PathBuf> {
+    let timestamp = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or_default();
+    let mut name = path.file_name().context("配置文件路径无效")?.to_os_string();
+    name.push(format!(".broken-{}", timestamp));
+    let backup = path.with_file_name(name);
+    
+    fs::rename(path, &backup)
+        .with_context(|| format!("备份配置文件失败: {:?}", backup))?;
+    Ok(backup)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::error::AppError;
+    use crate::window::FakeWindowProvider;
     
     #[test]
     fn test_default_config() {
         let config = AppConfig::default();
-        assert_eq!(config.ocr_engine, "Tesseract");
+        assert_eq!(config.ocr_engine, OcrEngine::Tesseract);
         assert_eq!(config.trigger_delay_ms, 300);
-        assert_eq!(config.auto_copy, true);
+        assert!(config.auto_copy);
     }
     
     #[test]
@@ -97,5 +1067,405 @@ mod tests {
         let deserialized: AppConfig = serde_json::from_str(&json).unwrap();
         assert_eq!(config.ocr_engine, deserialized.ocr_engine);
     }
+    
+    #[test]
+    fn test_migrate_v1() {
+        let json = r#"{"ocrEngine":"paddleocr","triggerDelayMs":500,"hotkey":"Alt","autoCopy":false}"#;
+        let config = AppConfig::from_json(json).unwrap();
+        assert_eq!(config.version, CONFIG_VERSION);
+        assert_eq!(config.ocr_engine, OcrEngine::PaddleOcr);
+        assert_eq!(config.trigger_delay_ms, 500);
+        assert!(!config.auto_copy);
+        
+        // 未知引擎回退为默认引擎，而不是整个文件无法读取
+        let config = AppConfig::from_json(r#"{"ocr_engine":"Nope"}"#).unwrap();
+        assert_eq!(config.ocr_engine, OcrEngine::Tesseract);
+        
+        // 当前版本的文件中的未知引擎只恢复该字段，其他设置保留
+        let json = r#"{
+            "version": 2,
+            "ocr_engine": "Foo",
+            "trigger_delay_ms": 300,
+            "ocr_timeouts_ms": {"Foo": 1000, "PaddleOCR": 60000},
+            "profiles": [{"name": "代码", "ocr_engine": "Bar"}]
+        }"#;
+        let config = AppConfig::from_json(json).unwrap();
+        assert_eq!(config.ocr_engine, OcrEngine::Tesseract);
+        assert_eq!(config.trigger_delay_ms, 300);
+        assert_eq!(config.ocr_timeouts_ms.keys().collect::<Vec<_>>(), [&OcrEngine::PaddleOcr]);
+        assert_eq!(config.profiles[0].ocr_engine, OcrEngine::Tesseract);
+        let Err(ConfigError::Invalid(errors)) = AppConfig::from_json_strict(json) else {
+            panic!("未知引擎应当校验失败");
+        };
+        let fields: Vec<&str> = errors.iter().map(|error| error.field.as_str()).collect();
+        assert_eq!(fields, ["ocr_engine", "ocr_timeouts_ms.Unknown", "profiles[0].ocr_engine"]);
+        
+        assert!(AppConfig::from_json(r#"{"version":0}"#).is_err());
+        assert!(AppConfig::from_json("[]").is_err());
+    }
+    
+    #[test]
+    fn test_validate() {
+        assert!(AppConfig::default().validate().is_ok());
+        
+        let mut config = AppConfig {
+            trigger_delay_ms: 60_000,
+            ocr_language: "chi sim".to_string(),
+            preprocess: PreprocessConfig {
+                binarize_window: 24,
+                ..Default::default()
+            },
+            hotkey_bindings: vec![HotkeyBindingConfig {
+                hotkey: " ".to_string(),
+                action: HotkeyAction::ShowHistory,
+            }],
+            ..Default::default()
+        };
+        
+        let ConfigError::Invalid(errors) = config.validate().unwrap_err() else {
+            panic!("应为字段错误");
+        };
+        let fields: Vec<&str> = errors.iter().map(|e| e.field.as_str()).collect();
+        assert_eq!(
+            fields,
+            vec!["ocr_language", "trigger_delay_ms", "hotkey_bindings[0].hotkey", "preprocess.binarize_window"]
+        );
+        
+        let json = serde_json::to_value(AppError::from(ConfigError::Invalid(errors))).unwrap();
+        assert_eq!(json["code"], "config_invalid");
+        assert_eq!(json["details"]["fields"][1]["field"], "trigger_delay_ms");
+        
+        // 读取文件时无效字段恢复为默认值
+        config.repair();
+        assert!(config.validate().is_ok());
+        assert!(config.hotkey_bindings.is_empty());
+    }
+    
+    #[test]
+    fn test_unreadable_file_is_backed_up() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("config.json");
+        fs::write(&path, "{ not json").unwrap();
+        
+        let config = AppConfig::load_from(&path).unwrap();
+        assert_eq!(config.version, CONFIG_VERSION);
+        assert!(!path.exists());
+        
+        let backups: Vec<_> = fs::read_dir(dir.path()).unwrap().map(|e| e.unwrap().path()).collect();
+        assert_eq!(backups.len(), 1);
+        assert_eq!(fs::read_to_string(&backups[0]).unwrap(), "{ not json");
+    }
+    
+    fn saved_with_delay(path: &Path, trigger_delay_ms: u32) {
+        let config = AppConfig { trigger_delay_ms, ..Default::default() };
+        config.save_to(path).unwrap();
+    }
+    
+    #[test]
+    fn test_save_keeps_backups() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("config.json");
+        for delay in 1..=5 {
+            saved_with_delay(&path, delay);
+        }
+        
+        assert_eq!(AppConfig::read_from(&path).unwrap().trigger_delay_ms, 5);
+        for n in 1..=CONFIG_BACKUP_COUNT {
+            let backup = AppConfig::read_from(&backup_path(&path, n)).unwrap();
+            assert_eq!(backup.trigger_delay_ms, 5 - n as u32);
+        }
+        assert!(!backup_path(&path, CONFIG_BACKUP_COUNT + 1).exists());
+        assert!(!dir.path().join("config.json.tmp").exists());
+        
+        // 损坏的文件不会进入备份
+        fs::write(&path, "garbage").unwrap();
+        saved_with_delay(&path, 6);
+        assert_eq!(AppConfig::read_from(&backup_path(&path, 1)).unwrap().trigger_delay_ms, 4);
+    }
+    
+    #[test]
+    fn test_newer_version_is_not_overwritten() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("config.json");
+        let content = r#"{"version": 99, "trigger_delay_ms": 300, "future_option": true}"#;
+        fs::write(&path, content).unwrap();
+        
+        let config = AppConfig::load_from(&path).unwrap();
+        assert_eq!(config.trigger_delay_ms, 300);
+        assert!(config.save_to(&path).is_err());
+        // 即使内存中的版本号被改回当前版本，也不覆盖新版本的文件
+        let downgraded = AppConfig { version: CONFIG_VERSION, ..config };
+        assert!(downgraded.save_to(&path).is_err());
+        assert_eq!(fs::read_to_string(&path).unwrap(), content);
+        assert!(!backup_path(&path, 1).exists());
+    }
+    
+    #[test]
+    fn test_truncated_file_recovers_from_backup() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("config.json");
+        saved_with_delay(&path, 100);
+        saved_with_delay(&path, 200);
+        
+        // 模拟写入中途断电
+        let content = fs::read_to_string(&path).unwrap();
+        fs::write(&path, &content[..content.len() / 2]).unwrap();
+        
+        let config = AppConfig::load_from(&path).unwrap();
+        assert_eq!(config.trigger_delay_ms, 100);
+        // 恢复的配置重新写回
+        assert_eq!(AppConfig::read_from(&path).unwrap().trigger_delay_ms, 100);
+    }
+    
+    #[test]
+    fn test_garbage_file_skips_bad_backups() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("config.json");
+        saved_with_delay(&path, 100);
+        saved_with_delay(&path, 200);
+        saved_with_delay(&path, 300);
+        
+        fs::write(&path, [0xff, 0x00, 0x12]).unwrap();
+        fs::write(backup_path(&path, 1), "{\"trigger_delay_ms\": ").unwrap();
+        
+        let config = AppConfig::load_from(&path).unwrap();
+        assert_eq!(config.trigger_delay_ms, 100);
+    }
+    
+    #[test]
+    fn test_missing_language_uses_default() {
+        let json = r#"{"ocr_engine":"Tesseract","trigger_delay_ms":300,"hotkey":"Alt","auto_copy":true}"#;
+        let config: AppConfig = serde_json::from_str(json).unwrap();
+        assert_eq!(config.ocr_language, "chi_sim+eng");
+        assert!(config.hotkey_bindings.is_empty());
+        assert_eq!(config.sequence_timeout_ms, 800);
+    }
+    
+    #[test]
+    fn test_profiles() {
+        let mut config = AppConfig::default();
+        config.create_profile(" 中文文档 ").unwrap();
+        config.ocr_engine = OcrEngine::PaddleOcr;
+        config.ocr_language = "eng".to_string();
+        config.create_profile("英文代码").unwrap();
+        assert_eq!(config.active_profile.as_deref(), Some("英文代码"));
+        
+        assert_eq!(config.create_profile("中文文档").unwrap_err().kind(), "invalid");
+        assert_eq!(config.activate_profile("日文游戏").unwrap_err().kind(), "unknown_profile");
+        
+        config.activate_profile("中文文档").unwrap();
+        assert_eq!(config.ocr_engine, OcrEngine::Tesseract);
+        assert_eq!(config.ocr_language, "chi_sim+eng");
+        
+        // 修改设置后写回当前方案
+        config.ocr_language = "chi_tra".to_string();
+        config.sync_active_profile();
+        assert_eq!(config.active_profile().unwrap().ocr_language, "chi_tra");
+        
+        config.duplicate_profile("中文文档", "繁体").unwrap();
+        config.rename_profile("中文文档", "简体").unwrap();
+        assert_eq!(config.active_profile.as_deref(), Some("简体"));
+        let names: Vec<&str> = config.profiles.iter().map(|p| p.name.as_str()).collect();
+        assert_eq!(names, vec!["简体", "繁体", "英文代码"]);
+        
+        config.delete_profile("简体").unwrap();
+        assert_eq!(config.active_profile, None);
+        assert_eq!(config.ocr_language, "chi_tra");
+        assert!(config.validate().is_ok());
+    }
+    
+    #[test]
+    fn test_profile_rules() {
+        let json = r#"{
+            "ocr_language": "chi_sim+eng",
+            "profiles": [
+                {"name": "代码", "ocr_language": "eng"},
+                {"name": "PDF", "ocr_engine": "PaddleOCR", "ocr_language": "chi_sim"}
+            ],
+            "profile_rules": [
+                {"window_class": "*term*", "profile": "代码"},
+                {"window_title": "*.pdf - *", "profile": "PDF"},
+                {"window_class": "firefox", "profile": "已删除"}
+            ]
+        }"#;
+        let mut config = AppConfig::from_json(json).unwrap();
+        // 指向不存在方案的规则在读取时丢弃
+        assert_eq!(config.profile_rules.len(), 2);
+        
+        let window = |class: &str, title: &str| WindowInfo {
+            class: class.to_string(),
+            title: title.to_string(),
+        };
+        let terminal = config.for_window(Some(&window("Gnome-terminal", "~/src")));
+        assert_eq!(terminal.ocr_language, "eng");
+        let pdf = config.for_window(Some(&window("okular", "论文.pdf - Okular")));
+        assert_eq!((pdf.ocr_engine, pdf.ocr_language.as_str()), (OcrEngine::PaddleOcr, "chi_sim"));
+        assert_eq!(config.for_window(Some(&window("firefox", "docs"))).ocr_language, "chi_sim+eng");
+        assert_eq!(config.for_window(None).ocr_language, "chi_sim+eng");
+        // 自动选择不改变当前方案
+        assert_eq!(pdf.active_profile, None);
+        
+        let provider = FakeWindowProvider::new("xfce4-terminal", "vim");
+        let recorded = window("okular", "论文.pdf - Okular");
+        // 记录的窗口优先于当前前台窗口
+        let trigger = config.trigger_window(Some(recorded.clone()), &provider);
+        assert_eq!(trigger.as_ref(), Some(&recorded));
+        let current = config.trigger_window(None, &provider);
+        assert_eq!(config.for_window(current.as_ref()).ocr_language, "eng");
+        // 没有规则时不使用窗口
+        assert_eq!(AppConfig::default().trigger_window(Some(recorded), &provider), None);
+        
+        config.rename_profile("代码", "终端").unwrap();
+        assert_eq!(config.profile_rules[0].profile, "终端");
+        config.delete_profile("PDF").unwrap();
+        assert_eq!(config.profile_rules.len(), 1);
+    }
+    
+    #[test]
+    fn test_resolve_config_path() {
+        let dir = tempfile::tempdir().unwrap();
+        let arg = dir.path().join("team.json");
+        let env = dir.path().join("env.json");
+        
+        assert_eq!(resolve_config_path(Some(&arg), Some(env.clone().into()), None).unwrap(), arg);
+        assert_eq!(resolve_config_path(None, Some(env.clone().into()), Some(dir.path())).unwrap(), env);
+        // 空的环境变量视为未设置
+        let default = resolve_config_path(None, Some(OsString::new()), Some(dir.path())).unwrap();
+        assert!(default.ends_with("screenocr-tauri/config.json"));
+        
+        fs::write(dir.path().join(PORTABLE_MARKER), "").unwrap();
+        assert_eq!(
+            resolve_config_path(None, None, Some(dir.path())).unwrap(),
+            dir.path().join("data").join("config.json")
+        );
+    }
+    
+    #[test]
+    fn test_export_and_replace() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("team.json");
+        let exported = AppConfig { trigger_delay_ms: 0, ocr_language: "eng".to_string(), ..Default::default() };
+        exported.export_to(&path).unwrap();
+        
+        let content = fs::read_to_string(&path).unwrap();
+        let local = AppConfig { auto_copy: false, ..Default::default() };
+        assert_eq!(local.imported(&content, ImportMode::Replace).unwrap(), exported);
+        
+        // 无效的配置不会被导入
+        let error = local.imported(r#"{"trigger_delay_ms": 999999}"#, ImportMode::Replace).unwrap_err();
+        assert_eq!(error.kind(), "invalid");
+    }
+    
+    #[test]
+    fn test_import_merge() {
+        let mut local = AppConfig { auto_copy: false, ..Default::default() };
+        local.create_profile("代码").unwrap();
+        local.create_profile("个人").unwrap();
+        local.hotkey_bindings.push(HotkeyBindingConfig {
+            hotkey: "Ctrl+Alt+P".to_string(),
+            action: HotkeyAction::ActivateProfile(1),
+        });
+        
+        let team = r#"{
+            "version": 2,
+            "preprocess": {"max_upscale": 2},
+            "profiles": [
+                {"name": "日文", "ocr_language": "jpn"},
+                {"name": "代码", "ocr_language": "eng"}
+            ],
+            "hotkey_bindings": [{"hotkey": "Ctrl+Alt+J", "action": {"activate_profile": 0}}]
+        }"#;
+        let merged = local.imported(team, ImportMode::Merge).unwrap();
+        
+        // 导入文件中没有的配置项保持不变
+        assert!(!merged.auto_copy);
+        assert_eq!(merged.preprocess.max_upscale, 2);
+        assert_eq!(merged.preprocess.steps, local.preprocess.steps);
+        
+        let names: Vec<&str> = merged.profiles.iter().map(|p| p.name.as_str()).collect();
+        assert_eq!(names, ["代码", "个人", "日文"]);
+        assert_eq!(merged.hotkey_bindings[0].action, HotkeyAction::ActivateProfile(2));
+        // 导入的设置同时保存到当前方案
+        assert_eq!(merged.active_profile().unwrap().preprocess.max_upscale, 2);
+        
+        // 当前方案被替换后使用新的设置
+        local.activate_profile("代码").unwrap();
+        let merged = local.imported(team, ImportMode::Merge).unwrap();
+        assert_eq!(merged.ocr_language, "eng");
+        
+        let error = local.imported("[1, 2]", ImportMode::Merge).unwrap_err();
+        assert_eq!(error.kind(), "parse");
+    }
+    
+    #[test]
+    fn test_profile_hotkeys() {
+        let mut config = AppConfig::default();
+        for name in ["A", "B", "C"] {
+            config.create_profile(name).unwrap();
+        }
+        config.profiles[2].hotkey = Some("Ctrl+Alt+3".to_string());
+        config.hotkey_bindings.push(HotkeyBindingConfig {
+            hotkey: "Ctrl+Alt+2".to_string(),
+            action: HotkeyAction::ActivateProfile(1),
+        });
+        
+        let bindings = config.all_hotkey_bindings();
+        assert_eq!(bindings.last().unwrap().action, HotkeyAction::ActivateProfile(2));
+        
+        // 删除前面的方案后序号前移
+        config.delete_profile("A").unwrap();
+        assert_eq!(config.hotkey_bindings[0].action, HotkeyAction::ActivateProfile(0));
+        assert_eq!(config.all_hotkey_bindings().last().unwrap().action, HotkeyAction::ActivateProfile(1));
+        
+        config.hotkey_bindings[0].action = HotkeyAction::ActivateProfile(9);
+        let ConfigError::Invalid(errors) = config.validate().unwrap_err() else {
+            panic!("应为字段错误");
+        };
+        assert_eq!(errors[0].field, "hotkey_bindings[0].action");
+    }
+    
+    #[test]
+    fn test_repair_profiles() {
+        let json = r#"{"profiles":[{"name":"文档","ocr_language":""},{"name":"文档","hotkey":""}],
+            "active_profile":"游戏"}"#;
+        let config = AppConfig::from_json(json).unwrap();
+        assert!(config.validate().is_ok());
+        assert_eq!(config.profiles[0].ocr_language, "chi_sim+eng");
+        assert_eq!(config.profiles[1].name, "方案 2");
+        assert_eq!(config.profiles[1].hotkey, None);
+        assert_eq!(config.active_profile, None);
+    }
+
+    #[test]
+    fn test_ocr_timeouts() {
+        let json = r#"{"ocr_timeouts_ms":{"PaddleOCR":60000,"Tesseract":10},"retrigger_policy":"cancel_previous"}"#;
+        let strict = AppConfig::from_json_strict(json).unwrap_err();
+        assert_eq!(strict.to_string(), "配置无效: ocr_timeouts_ms.Tesseract: 应在 1000 到 600000 之间");
+
+        let config = AppConfig::from_json(json).unwrap();
+        assert_eq!(config.retrigger_policy, RetriggerPolicy::CancelPrevious);
+        assert_eq!(config.ocr_timeout(OcrEngine::PaddleOcr), Duration::from_secs(60));
+        assert_eq!(config.ocr_timeout(OcrEngine::Tesseract), Duration::from_millis(DEFAULT_OCR_TIMEOUT_MS as u64));
+    }
+
+    #[test]
+    fn test_all_hotkey_bindings() {
+        let json = r#"{"ocr_engine":"Tesseract","trigger_delay_ms":300,"hotkey":"Alt","auto_copy":true,
+            "hotkey_bindings":[{"hotkey":"Ctrl+Shift+R","action":"repeat_last_region"}]}"#;
+        let mut config: AppConfig = serde_json::from_str(json).unwrap();
+        let bindings = config.all_hotkey_bindings();
+        assert_eq!(bindings.len(), 2);
+        assert_eq!(bindings[0].action, HotkeyAction::FullScreenOcr);
+        assert_eq!(bindings[1].action, HotkeyAction::RepeatLastRegion);
+        
+        assert_eq!(config.hotkey_binding_position(None), Some(0));
+        assert_eq!(config.hotkey_binding_position(Some(0)), Some(1));
+        assert_eq!(config.hotkey_binding_position(Some(1)), None);
+        
+        config.hotkey.clear();
+        assert_eq!(config.all_hotkey_bindings().len(), 1);
+        assert_eq!(config.hotkey_binding_position(None), None);
+        assert_eq!(config.hotkey_binding_position(Some(0)), Some(0));
+    }
 }
 
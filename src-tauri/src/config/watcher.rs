@@ -0,0 +1,121 @@
+//! 监听配置文件，手动修改后无需重启即可生效
+
+use anyhow::{Context, Result};
+use notify::{Event, EventKind, RecommendedWatcher, RecursiveMode, Watcher};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::mpsc;
+use std::time::Duration;
+use super::{AppConfig, ConfigError};
+
+/// 合并编辑器保存时连续产生的多个事件
+const DEBOUNCE: Duration = Duration::from_millis(300);
+
+/// 配置文件监听器，销毁时停止监听
+pub struct ConfigWatcher {
+    _watcher: RecommendedWatcher,
+}
+
+impl ConfigWatcher {
+    /// 监听 `path`，文件内容变化时调用 `on_change`
+    ///
+    /// 监听的是所在目录而不是文件本身，这样原子保存（写临时文件再改名）之后仍能继续收到事件。
+    /// 内容与上一次相同的写入会被忽略；内容变化但与内存中的配置相同（例如本程序自己保存）
+    /// 的情况需要调用方自行比较。
+    pub fn start(
+        path: PathBuf,
+        on_change: impl Fn(Result<AppConfig, ConfigError>) + Send + 'static,
+    ) -> Result<Self> {
+        let dir = path.parent().context("配置文件路径无效")?.to_path_buf();
+        let (tx, rx) = mpsc::channel();
+        let mut watcher = notify::recommended_watcher(tx).context("创建配置文件监听器失败")?;
+        watcher
+            .watch(&dir, RecursiveMode::NonRecursive)
+            .with_context(|| format!("监听配置目录失败: {:?}", dir))?;
+
+        std::thread::spawn(move || {
+            let mut last_content = fs::read_to_string(&path).ok();
+            // 监听器销毁后发送端随之关闭，线程退出
+            while let Ok(event) = rx.recv() {
+                if !is_config_event(&event, &path) {
+                    continue;
+                }
+                while rx.recv_timeout(DEBOUNCE).is_ok() {}
+
+                // 文件被删除或正在被替换
+                let Ok(content) = fs::read_to_string(&path) else { continue };
+                if last_content.as_deref() == Some(content.as_str()) {
+                    continue;
+                }
+                println!("📝 检测到配置文件变化: {:?}", path);
+                let config = AppConfig::from_json_strict(&content);
+                last_content = Some(content);
+                on_change(config);
+            }
+        });
+
+        println!("👀 正在监听配置文件: {:?}", dir);
+        Ok(Self { _watcher: watcher })
+    }
+}
+
+/// 是否是配置文件本身的创建、修改或改名（忽略临时文件和备份）
+fn is_config_event(event: &notify::Result<Event>, path: &Path) -> bool {
+    let Ok(event) = event else { return false };
+    matches!(event.kind, EventKind::Create(_) | EventKind::Modify(_))
+        && event.paths.iter().any(|p| p.file_name() == path.file_name())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use notify::event::{AccessKind, CreateKind, ModifyKind, RenameMode};
+
+    fn event(kind: EventKind, paths: &[&str]) -> notify::Result<Event> {
+        let mut event = Event::new(kind);
+        for path in paths {
+            event = event.add_path(PathBuf::from(path));
+        }
+        Ok(event)
+    }
+
+    #[test]
+    fn test_is_config_event() {
+        let path = Path::new("/cfg/config.json");
+        assert!(is_config_event(&event(EventKind::Modify(ModifyKind::Any), &["/cfg/config.json"]), path));
+        // 原子保存：临时文件改名为配置文件
+        assert!(is_config_event(
+            &event(
+                EventKind::Modify(ModifyKind::Name(RenameMode::Both)),
+                &["/cfg/config.json.tmp", "/cfg/config.json"]
+            ),
+            path
+        ));
+        assert!(!is_config_event(&event(EventKind::Create(CreateKind::File), &["/cfg/config.json.tmp"]), path));
+        assert!(!is_config_event(&event(EventKind::Create(CreateKind::File), &["/cfg/config.json.bak.1"]), path));
+        assert!(!is_config_event(&event(EventKind::Access(AccessKind::Any), &["/cfg/config.json"]), path));
+    }
+
+    #[test]
+    fn test_reports_external_changes() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("config.json");
+        AppConfig::default().save_to(&path).unwrap();
+
+        let (tx, rx) = mpsc::channel();
+        let _watcher = ConfigWatcher::start(path.clone(), move |result| {
+            let _ = tx.send(result);
+        })
+        .unwrap();
+
+        let edited = AppConfig { trigger_delay_ms: 500, ..Default::default() };
+        edited.save_to(&path).unwrap();
+        let received = rx.recv_timeout(Duration::from_secs(5)).unwrap().unwrap();
+        assert_eq!(received, edited);
+
+        // 超出范围的修改报告为字段错误
+        fs::write(&path, r#"{"trigger_delay_ms": 999999}"#).unwrap();
+        let error = rx.recv_timeout(Duration::from_secs(5)).unwrap().unwrap_err();
+        assert_eq!(error.kind(), "invalid");
+    }
+}
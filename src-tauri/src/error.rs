@@ -0,0 +1,294 @@
+//! 返回给前端的错误，序列化为 `{ code, message, details }`
+//!
+//! `code` 是稳定的错误代码，前端据此做不同处理；`message` 按界面语言本地化，可以直接显示；
+//! `details` 是错误相关的结构化数据（例如无效的字段、被占用的热键），没有时为 `null`。
+
+use serde::ser::SerializeStruct;
+use serde::Serialize;
+use serde_json::{json, Value};
+use std::sync::RwLock;
+use thiserror::Error;
+use crate::config::ConfigError;
+use crate::hotkey::HotkeyError;
+use crate::screenshot::{CaptureRegion, CropError};
+
+/// 错误信息的语言
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Locale {
+    Zh,
+    En,
+}
+
+static LOCALE: RwLock<Option<Locale>> = RwLock::new(None);
+
+impl Locale {
+    /// 按 BCP 47 / POSIX 语言标记选择，只有英文环境使用英文
+    pub fn parse(tag: &str) -> Self {
+        if tag.trim().to_lowercase().starts_with("en") {
+            Self::En
+        } else {
+            Self::Zh
+        }
+    }
+
+    /// 根据 `LC_ALL`、`LC_MESSAGES`、`LANG` 判断系统语言
+    ///
+    /// Windows 和 macOS 从图形界面启动时通常没有这些变量，由前端按界面语言调用 [`Locale::set`]。
+    pub fn from_env() -> Self {
+        ["LC_ALL", "LC_MESSAGES", "LANG"]
+            .iter()
+            .filter_map(|var| std::env::var(var).ok())
+            .find(|value| !value.is_empty())
+            .map_or(Self::Zh, |value| Self::parse(&value))
+    }
+
+    /// 错误信息使用的语言，未设置时按系统语言
+    pub fn current() -> Self {
+        if let Some(locale) = *LOCALE.read().unwrap() {
+            return locale;
+        }
+        *LOCALE.write().unwrap().get_or_insert_with(Self::from_env)
+    }
+
+    /// 设置错误信息的语言，之后生成的错误信息都使用它
+    pub fn set(locale: Self) {
+        *LOCALE.write().unwrap() = Some(locale);
+    }
+}
+
+/// 命令和识别流程的错误
+#[derive(Debug, Error)]
+pub enum AppError {
+    /// 截图或读取剪贴板图片失败
+    #[error("获取图像失败: {0:#}")]
+    Capture(anyhow::Error),
+    /// 裁剪区域超出图像范围
+    #[error(
+        "裁剪区域 {}x{} at ({}, {}) 超出图像范围 {image_width}x{image_height}",
+        region.width, region.height, region.x, region.y
+    )]
+    CropOutOfBounds {
+        region: CaptureRegion,
+        image_width: u32,
+        image_height: u32,
+    },
+    /// 引擎不存在或当前平台、构建不支持
+    #[error("OCR 引擎 {engine} 不可用: {reason}")]
+    EngineUnavailable { engine: String, reason: String },
+    /// 引擎缺少识别语言需要的语言包
+    #[error("OCR 引擎 {engine} 缺少语言包: {}", missing.join(", "))]
+    LanguageMissing { engine: String, missing: Vec<String> },
+    #[error("图像预处理失败: {0:#}")]
+    Preprocess(anyhow::Error),
+    #[error("OCR 识别失败: {0:#}")]
+    Recognition(anyhow::Error),
+    /// 识别超过了允许的时间
+    #[error("识别超时（{timeout_ms} 毫秒）")]
+    Timeout { timeout_ms: u64 },
+    /// 识别被用户取消
+    #[error("识别已取消")]
+    Cancelled,
+    /// 上一次识别还没有完成，本次触发被忽略
+    #[error("上一次识别还没有完成")]
+    Busy,
+    #[error(transparent)]
+    Config(#[from] ConfigError),
+    #[error(transparent)]
+    Hotkey(#[from] HotkeyError),
+    /// 重复识别或实时识别前还没有选择过区域
+    #[error("还没有选择识别区域")]
+    NoRegion,
+    #[error("历史记录不可用")]
+    HistoryUnavailable,
+    #[error("读取或写入历史记录失败: {0:#}")]
+    History(anyhow::Error),
+    /// 其他内部错误
+    #[error("{0:#}")]
+    Internal(anyhow::Error),
+}
+
+impl AppError {
+    /// 稳定的错误代码
+    pub fn code(&self) -> &'static str {
+        match self {
+            Self::Capture(_) => "capture_failed",
+            Self::CropOutOfBounds { .. } => "crop_out_of_bounds",
+            Self::EngineUnavailable { .. } => "engine_unavailable",
+            Self::LanguageMissing { .. } => "language_missing",
+            Self::Preprocess(_) => "preprocess_failed",
+            Self::Recognition(_) => "recognition_failed",
+            Self::Timeout { .. } => "timeout",
+            Self::Cancelled => "cancelled",
+            Self::Busy => "busy",
+            Self::Config(ConfigError::Invalid(_) | ConfigError::Parse(_)) => "config_invalid",
+            Self::Config(ConfigError::Save(_)) => "config_save_failed",
+            Self::Config(ConfigError::UnknownProfile(_)) => "profile_not_found",
+            Self::Hotkey(HotkeyError::Duplicate { .. } | HotkeyError::AlreadyTaken { .. }) => "hotkey_conflict",
+            Self::Hotkey(HotkeyError::InvalidSyntax { .. }) => "hotkey_invalid",
+            Self::Hotkey(HotkeyError::OsDenied { .. } | HotkeyError::Unavailable { .. }) => "hotkey_unavailable",
+            Self::NoRegion => "no_region",
+            Self::HistoryUnavailable => "history_unavailable",
+            Self::History(_) => "history_failed",
+            Self::Internal(_) => "internal",
+        }
+    }
+
+    /// 错误的结构化数据
+    pub fn details(&self) -> Value {
+        match self {
+            Self::Capture(e) | Self::Preprocess(e) | Self::Recognition(e) | Self::History(e) | Self::Internal(e) => {
+                json!({ "cause": format!("{:#}", e) })
+            }
+            Self::CropOutOfBounds { region, image_width, image_height } => json!({
+                "region": region,
+                "image_width": image_width,
+                "image_height": image_height,
+            }),
+            Self::EngineUnavailable { engine, reason } => json!({ "engine": engine, "reason": reason }),
+            Self::LanguageMissing { engine, missing } => json!({ "engine": engine, "missing": missing }),
+            Self::Timeout { timeout_ms } => json!({ "timeout_ms": timeout_ms }),
+            Self::Config(e) => {
+                let fields = match e {
+                    ConfigError::Invalid(fields) => fields.as_slice(),
+                    _ => &[],
+                };
+                json!({ "kind": e.kind(), "fields": fields })
+            }
+            Self::Hotkey(e) => json!({ "kind": e.kind(), "hotkey": e.hotkey() }),
+            Self::Cancelled | Self::Busy | Self::NoRegion | Self::HistoryUnavailable => Value::Null,
+        }
+    }
+
+    /// 指定语言的错误信息，系统返回的原因（文件路径、系统错误等）保持原样
+    pub fn message(&self, locale: Locale) -> String {
+        if locale == Locale::Zh {
+            return self.to_string();
+        }
+        match self {
+            Self::Capture(e) => format!("Failed to capture the image: {:#}", e),
+            Self::CropOutOfBounds { region, image_width, image_height } => format!(
+                "Crop area {}x{} at ({}, {}) exceeds the image size {}x{}",
+                region.width, region.height, region.x, region.y, image_width, image_height
+            ),
+            Self::EngineUnavailable { engine, reason } => format!("OCR engine {} is unavailable: {}", engine, reason),
+            Self::LanguageMissing { engine, missing } => {
+                format!("OCR engine {} is missing language data: {}", engine, missing.join(", "))
+            }
+            Self::Preprocess(e) => format!("Image preprocessing failed: {:#}", e),
+            Self::Recognition(e) => format!("Text recognition failed: {:#}", e),
+            Self::Timeout { timeout_ms } => format!("Recognition timed out after {} ms", timeout_ms),
+            Self::Cancelled => "Recognition was cancelled".to_string(),
+            Self::Busy => "The previous recognition has not finished yet".to_string(),
+            Self::Config(ConfigError::Invalid(fields)) => {
+                let fields: Vec<&str> = fields.iter().map(|field| field.field.as_str()).collect();
+                format!("Invalid configuration: {}", fields.join(", "))
+            }
+            Self::Config(ConfigError::Parse(e)) => format!("Failed to parse the configuration: {:#}", e),
+            Self::Config(ConfigError::Save(e)) => format!("Failed to save the configuration: {:#}", e),
+            Self::Config(ConfigError::UnknownProfile(name)) => format!("Profile \"{}\" does not exist", name),
+            Self::Hotkey(HotkeyError::InvalidSyntax { hotkey, reason }) => {
+                format!("Invalid hotkey \"{}\": {}", hotkey, reason)
+            }
+            Self::Hotkey(HotkeyError::Duplicate { hotkey, .. }) => {
+                format!("Hotkey \"{}\" is bound to more than one action", hotkey)
+            }
+            Self::Hotkey(HotkeyError::AlreadyTaken { hotkey }) => {
+                format!("Hotkey \"{}\" is already used by another application", hotkey)
+            }
+            Self::Hotkey(HotkeyError::OsDenied { hotkey, reason }) => {
+                format!("The system refused to register hotkey \"{}\": {}", hotkey, reason)
+            }
+            Self::Hotkey(HotkeyError::Unavailable { reason }) => format!("Global hotkeys are unavailable: {}", reason),
+            Self::NoRegion => "No recognition area has been selected yet".to_string(),
+            Self::HistoryUnavailable => "History is unavailable".to_string(),
+            Self::History(e) => format!("Failed to access history: {:#}", e),
+            Self::Internal(e) => format!("{:#}", e),
+        }
+    }
+}
+
+impl From<CropError> for AppError {
+    fn from(error: CropError) -> Self {
+        match error {
+            CropError::OutOfBounds { region, image_width, image_height } => Self::CropOutOfBounds {
+                region,
+                image_width,
+                image_height,
+            },
+            other => Self::Capture(anyhow::Error::new(other).context("裁剪截图失败")),
+        }
+    }
+}
+
+impl Serialize for AppError {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let mut state = serializer.serialize_struct("AppError", 3)?;
+        state.serialize_field("code", self.code())?;
+        state.serialize_field("message", &self.message(Locale::current()))?;
+        state.serialize_field("details", &self.details())?;
+        state.end()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::{FieldError, HotkeyAction};
+
+    #[test]
+    fn test_locale_parse() {
+        assert_eq!(Locale::parse("en_US.UTF-8"), Locale::En);
+        assert_eq!(Locale::parse("en"), Locale::En);
+        assert_eq!(Locale::parse("zh_CN.UTF-8"), Locale::Zh);
+        assert_eq!(Locale::parse("C"), Locale::Zh);
+    }
+
+    #[test]
+    fn test_locale_set() {
+        let error = AppError::NoRegion;
+        Locale::set(Locale::En);
+        assert_eq!(error.message(Locale::current()), error.message(Locale::En));
+        // 前端可以在生成过错误信息之后再切换
+        Locale::set(Locale::Zh);
+        assert_eq!(serde_json::to_value(&error).unwrap()["message"], error.message(Locale::Zh));
+    }
+
+    #[test]
+    fn test_codes_and_details() {
+        let error = AppError::from(ConfigError::Invalid(vec![FieldError::new("trigger_delay_ms", "不能超过 5000")]));
+        assert_eq!(error.code(), "config_invalid");
+        assert_eq!(error.details()["fields"][0]["field"], "trigger_delay_ms");
+
+        let error = AppError::from(HotkeyError::Duplicate {
+            hotkey: "Ctrl+K".to_string(),
+            first: HotkeyAction::FullScreenOcr,
+            second: HotkeyAction::ShowHistory,
+        });
+        assert_eq!(error.code(), "hotkey_conflict");
+        assert_eq!(error.details(), json!({ "kind": "duplicate", "hotkey": "Ctrl+K" }));
+
+        let error = AppError::CropOutOfBounds {
+            region: CaptureRegion { x: 90, y: 0, width: 20, height: 10 },
+            image_width: 100,
+            image_height: 50,
+        };
+        assert_eq!(error.code(), "crop_out_of_bounds");
+        assert_eq!(error.details()["region"]["x"], 90);
+        assert_eq!(AppError::Cancelled.details(), Value::Null);
+    }
+
+    #[test]
+    fn test_localized_messages() {
+        let error = AppError::LanguageMissing {
+            engine: "Tesseract".to_string(),
+            missing: vec!["jpn".to_string()],
+        };
+        assert_eq!(error.message(Locale::Zh), "OCR 引擎 Tesseract 缺少语言包: jpn");
+        assert_eq!(error.message(Locale::En), "OCR engine Tesseract is missing language data: jpn");
+
+        let value = serde_json::to_value(AppError::Timeout { timeout_ms: 3000 }).unwrap();
+        assert_eq!(value["code"], "timeout");
+        assert_eq!(value["details"]["timeout_ms"], 3000);
+        assert!(value["message"].as_str().unwrap().contains("3000"));
+    }
+}
@@ -0,0 +1,380 @@
+use anyhow::{Context, Result};
+use base64::Engine;
+use image::{imageops, imageops::FilterType};
+use rusqlite::{params, Connection, OptionalExtension, Row};
+use serde::{Deserialize, Serialize};
+use std::io::Cursor;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::time::{SystemTime, UNIX_EPOCH};
+use crate::config::AppConfig;
+use crate::screenshot::ScreenCapture;
+
+/// 缩略图最大宽度
+const THUMBNAIL_MAX_WIDTH: u32 = 240;
+
+/// 识别区域（虚拟桌面坐标）
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct HistoryRegion {
+    pub x: i32,
+    pub y: i32,
+    pub width: u32,
+    pub height: u32,
+}
+
+/// 历史记录条目
+#[derive(Debug, Clone, Serialize)]
+pub struct HistoryEntry {
+    pub id: i64,
+    pub text: String,
+    pub confidence: f32,
+    pub engine: String,
+    pub language: String,
+    /// 识别时间（Unix 毫秒）
+    pub created_at: i64,
+    pub region: Option<HistoryRegion>,
+    /// PNG 缩略图的 Base64 编码
+    pub thumbnail: Option<String>,
+    pub pinned: bool,
+}
+
+/// 待写入的历史记录
+#[derive(Debug, Clone)]
+pub struct NewHistoryEntry {
+    pub text: String,
+    pub confidence: f32,
+    pub engine: String,
+    pub language: String,
+    pub region: Option<HistoryRegion>,
+    /// PNG 缩略图
+    pub thumbnail: Option<Vec<u8>>,
+}
+
+/// OCR 历史存储（SQLite）
+pub struct HistoryStore {
+    conn: Mutex<Connection>,
+}
+
+impl HistoryStore {
+    /// 历史数据库路径，与 config.json 位于同一目录
+    pub fn default_path() -> Result<PathBuf> {
+        let config_path = AppConfig::get_config_path()?;
+        let dir = config_path.parent().context("无法获取配置目录")?;
+        Ok(dir.join("history.sqlite"))
+    }
+
+    /// 打开（或创建）历史数据库
+    pub fn open(path: &Path) -> Result<Self> {
+        let conn = Connection::open(path)
+            .with_context(|| format!("打开历史数据库失败: {:?}", path))?;
+        Self::init(conn)
+    }
+
+    /// 内存数据库（用于测试）
+    pub fn open_in_memory() -> Result<Self> {
+        Self::init(Connection::open_in_memory()?)
+    }
+
+    fn init(conn: Connection) -> Result<Self> {
+        // 全文索引使用 trigram 分词，中文等无空格文本也能按子串搜索
+        conn.execute_batch(
+            "PRAGMA journal_mode = WAL;
+             CREATE TABLE IF NOT EXISTS history (
+                 id INTEGER PRIMARY KEY AUTOINCREMENT,
+                 text TEXT NOT NULL,
+                 confidence REAL NOT NULL,
+                 engine TEXT NOT NULL,
+                 language TEXT NOT NULL,
+                 created_at INTEGER NOT NULL,
+                 region_x INTEGER,
+                 region_y INTEGER,
+                 region_width INTEGER,
+                 region_height INTEGER,
+                 thumbnail BLOB,
+                 pinned INTEGER NOT NULL DEFAULT 0
+             );
+             CREATE INDEX IF NOT EXISTS idx_history_created_at ON history(created_at);
+             CREATE VIRTUAL TABLE IF NOT EXISTS history_fts USING fts5(
+                 text, content='history', content_rowid='id', tokenize='trigram'
+             );
+             CREATE TRIGGER IF NOT EXISTS history_ai AFTER INSERT ON history BEGIN
+                 INSERT INTO history_fts(rowid, text) VALUES (new.id, new.text);
+             END;
+             CREATE TRIGGER IF NOT EXISTS history_ad AFTER DELETE ON history BEGIN
+                 INSERT INTO history_fts(history_fts, rowid, text) VALUES ('delete', old.id, old.text);
+             END;",
+        )
+        .context("初始化历史数据库失败")?;
+
+        Ok(Self {
+            conn: Mutex::new(conn),
+        })
+    }
+
+    /// 写入一条记录，并按保留上限清理旧记录（`retention` 为 0 时不限制）
+    pub fn record(&self, entry: NewHistoryEntry, retention: usize) -> Result<i64> {
+        let id = {
+            let conn = self.conn.lock().unwrap();
+            let region = entry.region;
+            conn.execute(
+                "INSERT INTO history (text, confidence, engine, language, created_at,
+                                      region_x, region_y, region_width, region_height, thumbnail)
+                 VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10)",
+                params![
+                    entry.text,
+                    entry.confidence,
+                    entry.engine,
+                    entry.language,
+                    now_millis(),
+                    region.map(|r| r.x),
+                    region.map(|r| r.y),
+                    region.map(|r| r.width),
+                    region.map(|r| r.height),
+                    entry.thumbnail,
+                ],
+            )
+            .context("写入历史记录失败")?;
+            conn.last_insert_rowid()
+        };
+
+        if retention > 0 {
+            self.enforce_retention(retention)?;
+        }
+        Ok(id)
+    }
+
+    /// 按时间倒序列出记录，置顶的记录排在最前
+    pub fn list(&self, limit: usize, offset: usize) -> Result<Vec<HistoryEntry>> {
+        let conn = self.conn.lock().unwrap();
+        let mut stmt = conn.prepare(
+            "SELECT id, text, confidence, engine, language, created_at,
+                    region_x, region_y, region_width, region_height, thumbnail, pinned
+             FROM history ORDER BY pinned DESC, created_at DESC, id DESC LIMIT ?1 OFFSET ?2",
+        )?;
+        let entries = stmt
+            .query_map(params![limit as i64, offset as i64], entry_from_row)?
+            .collect::<rusqlite::Result<Vec<_>>>()?;
+        Ok(entries)
+    }
+
+    /// 全文搜索，结果按时间倒序
+    pub fn search(&self, query: &str, limit: usize) -> Result<Vec<HistoryEntry>> {
+        let query = query.trim();
+        if query.is_empty() {
+            return self.list(limit, 0);
+        }
+
+        let conn = self.conn.lock().unwrap();
+        // trigram 索引要求查询至少 3 个字符，更短的查询退化为 LIKE
+        let (sql, pattern) = if query.chars().count() >= 3 {
+            (
+                "SELECT h.id, h.text, h.confidence, h.engine, h.language, h.created_at,
+                        h.region_x, h.region_y, h.region_width, h.region_height, h.thumbnail, h.pinned
+                 FROM history_fts f JOIN history h ON h.id = f.rowid
+                 WHERE history_fts MATCH ?1
+                 ORDER BY h.created_at DESC, h.id DESC LIMIT ?2",
+                format!("\"{}\"", query.replace('"', "\"\"")),
+            )
+        } else {
+            (
+                "SELECT id, text, confidence, engine, language, created_at,
+                        region_x, region_y, region_width, region_height, thumbnail, pinned
+                 FROM history WHERE text LIKE ?1 ESCAPE '\\'
+                 ORDER BY created_at DESC, id DESC LIMIT ?2",
+                format!(
+                    "%{}%",
+                    query.replace('\\', "\\\\").replace('%', "\\%").replace('_', "\\_")
+                ),
+            )
+        };
+
+        let mut stmt = conn.prepare(sql)?;
+        let entries = stmt
+            .query_map(params![pattern, limit as i64], entry_from_row)?
+            .collect::<rusqlite::Result<Vec<_>>>()?;
+        Ok(entries)
+    }
+
+    /// 按 ID 获取记录
+    pub fn get(&self, id: i64) -> Result<Option<HistoryEntry>> {
+        let conn = self.conn.lock().unwrap();
+        let entry = conn
+            .query_row(
+                "SELECT id, text, confidence, engine, language, created_at,
+                        region_x, region_y, region_width, region_height, thumbnail, pinned
+                 FROM history WHERE id = ?1",
+                params![id],
+                entry_from_row,
+            )
+            .optional()?;
+        Ok(entry)
+    }
+
+    /// 置顶或取消置顶，返回记录是否存在
+    pub fn set_pinned(&self, id: i64, pinned: bool) -> Result<bool> {
+        let conn = self.conn.lock().unwrap();
+        let changed = conn.execute(
+            "UPDATE history SET pinned = ?1 WHERE id = ?2",
+            params![pinned, id],
+        )?;
+        Ok(changed > 0)
+    }
+
+    /// 删除记录，返回记录是否存在
+    pub fn delete(&self, id: i64) -> Result<bool> {
+        let conn = self.conn.lock().unwrap();
+        let changed = conn.execute("DELETE FROM history WHERE id = ?1", params![id])?;
+        Ok(changed > 0)
+    }
+
+    /// 清空历史，返回删除的条数
+    pub fn purge(&self, keep_pinned: bool) -> Result<usize> {
+        let conn = self.conn.lock().unwrap();
+        let sql = if keep_pinned {
+            "DELETE FROM history WHERE pinned = 0"
+        } else {
+            "DELETE FROM history"
+        };
+        Ok(conn.execute(sql, [])?)
+    }
+
+    /// 只保留最新的 `limit` 条未置顶记录，返回删除的条数
+    pub fn enforce_retention(&self, limit: usize) -> Result<usize> {
+        let conn = self.conn.lock().unwrap();
+        let removed = conn.execute(
+            "DELETE FROM history WHERE pinned = 0 AND id NOT IN (
+                 SELECT id FROM history WHERE pinned = 0
+                 ORDER BY created_at DESC, id DESC LIMIT ?1
+             )",
+            params![limit as i64],
+        )?;
+        Ok(removed)
+    }
+}
+
+fn entry_from_row(row: &Row) -> rusqlite::Result<HistoryEntry> {
+    let region = match (
+        row.get::<_, Option<i32>>(6)?,
+        row.get::<_, Option<i32>>(7)?,
+        row.get::<_, Option<u32>>(8)?,
+        row.get::<_, Option<u32>>(9)?,
+    ) {
+        (Some(x), Some(y), Some(width), Some(height)) => Some(HistoryRegion { x, y, width, height }),
+        _ => None,
+    };
+    let thumbnail: Option<Vec<u8>> = row.get(10)?;
+
+    Ok(HistoryEntry {
+        id: row.get(0)?,
+        text: row.get(1)?,
+        confidence: row.get(2)?,
+        engine: row.get(3)?,
+        language: row.get(4)?,
+        created_at: row.get(5)?,
+        region,
+        thumbnail: thumbnail.map(|png| base64::engine::general_purpose::STANDARD.encode(png)),
+        pinned: row.get(11)?,
+    })
+}
+
+fn now_millis() -> i64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_millis() as i64)
+        .unwrap_or(0)
+}
+
+/// 生成 PNG 缩略图，宽度不超过 240 像素
+pub fn make_thumbnail(capture: &ScreenCapture) -> Result<Vec<u8>> {
+    let img = capture.view()?;
+
+    let mut buffer = Vec::new();
+    if img.width() > THUMBNAIL_MAX_WIDTH {
+        let height = ((img.height() as u64 * THUMBNAIL_MAX_WIDTH as u64) / img.width() as u64).max(1) as u32;
+        imageops::resize(&img, THUMBNAIL_MAX_WIDTH, height, FilterType::Triangle)
+            .write_to(&mut Cursor::new(&mut buffer), image::ImageOutputFormat::Png)?;
+    } else {
+        img.write_to(&mut Cursor::new(&mut buffer), image::ImageOutputFormat::Png)?;
+    }
+    Ok(buffer)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entry(text: &str) -> NewHistoryEntry {
+        NewHistoryEntry {
+            text: text.to_string(),
+            confidence: 0.9,
+            engine: "Tesseract".to_string(),
+            language: "chi_sim+eng".to_string(),
+            region: Some(HistoryRegion { x: -10, y: 20, width: 100, height: 50 }),
+            thumbnail: None,
+        }
+    }
+
+    #[test]
+    fn test_record_and_list() {
+        let store = HistoryStore::open_in_memory().unwrap();
+        let first = store.record(entry("Hello World"), 0).unwrap();
+        let second = store.record(entry("你好世界"), 0).unwrap();
+
+        let entries = store.list(10, 0).unwrap();
+        assert_eq!(entries.len(), 2);
+        assert_eq!(entries[0].id, second);
+        assert_eq!(entries[1].id, first);
+        assert_eq!(entries[1].region.unwrap().x, -10);
+    }
+
+    #[test]
+    fn test_search_substring() {
+        let store = HistoryStore::open_in_memory().unwrap();
+        store.record(entry("Hello World"), 0).unwrap();
+        store.record(entry("屏幕文字识别工具"), 0).unwrap();
+
+        assert_eq!(store.search("文字识别", 10).unwrap().len(), 1);
+        assert_eq!(store.search("orl", 10).unwrap().len(), 1);
+        // 少于 3 个字符时使用 LIKE
+        assert_eq!(store.search("屏幕", 10).unwrap().len(), 1);
+        assert!(store.search("不存在", 10).unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_retention_keeps_pinned() {
+        let store = HistoryStore::open_in_memory().unwrap();
+        let pinned = store.record(entry("important"), 0).unwrap();
+        assert!(store.set_pinned(pinned, true).unwrap());
+
+        for i in 0..5 {
+            store.record(entry(&format!("entry {}", i)), 3).unwrap();
+        }
+
+        let entries = store.list(10, 0).unwrap();
+        assert_eq!(entries.len(), 4);
+        assert_eq!(entries[0].id, pinned);
+
+        assert_eq!(store.purge(true).unwrap(), 3);
+        assert_eq!(store.list(10, 0).unwrap().len(), 1);
+    }
+
+    #[test]
+    fn test_delete_removes_from_search() {
+        let store = HistoryStore::open_in_memory().unwrap();
+        let id = store.record(entry("delete me"), 0).unwrap();
+
+        assert!(store.delete(id).unwrap());
+        assert!(!store.delete(id).unwrap());
+        assert!(store.get(id).unwrap().is_none());
+        assert!(store.search("delete", 10).unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_make_thumbnail() {
+        let capture = ScreenCapture::new(480, 100, vec![128; 480 * 100 * 4], 0, 0);
+        let png = make_thumbnail(&capture).unwrap();
+        let thumbnail = image::load_from_memory(&png).unwrap();
+        assert_eq!(thumbnail.width(), 240);
+        assert_eq!(thumbnail.height(), 50);
+    }
+}
@@ -1,180 +1,1276 @@
 use global_hotkey::{
     hotkey::{HotKey, Modifiers, Code},
-    GlobalHotKeyManager, GlobalHotKeyEvent,
+    GlobalHotKeyManager, GlobalHotKeyEvent, HotKeyState,
 };
-use std::sync::Arc;
-use tauri::AppHandle;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fmt;
+use std::sync::mpsc::{self, RecvTimeoutError};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use tauri::{AppHandle, Manager};
+use thiserror::Error;
+use crate::config::{HotkeyAction, HotkeyBindingConfig};
+
+mod keys;
+mod sequence;
+pub use keys::{key_name, supported_keys};
+pub use sequence::{SequenceMatcher, SequenceOutcome};
+
+/// 单独修饰键的最短触发阈值，避免与 Alt+Tab 等组合键冲突
+const MIN_MODIFIER_THRESHOLD_MS: u32 = 150;
+/// 按住检测的轮询间隔
+const MODIFIER_POLL_INTERVAL: Duration = Duration::from_millis(10);
+/// 按键序列超时检测的轮询间隔
+const SEQUENCE_POLL_INTERVAL: Duration = Duration::from_millis(50);
+
+/// 可以单独作为热键的修饰键（不区分左右）
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ModifierKey {
+    Alt,
+    Ctrl,
+    Shift,
+    Super,
+}
+
+impl ModifierKey {
+    /// 格式化时的顺序
+    pub const ALL: [ModifierKey; 4] = [Self::Ctrl, Self::Alt, Self::Shift, Self::Super];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::Alt => "Alt",
+            Self::Ctrl => "Ctrl",
+            Self::Shift => "Shift",
+            Self::Super => "Super",
+        }
+    }
+
+    fn parse(name: &str) -> Option<Self> {
+        match name.to_uppercase().as_str() {
+            "ALT" => Some(Self::Alt),
+            "CTRL" | "CONTROL" => Some(Self::Ctrl),
+            "SHIFT" => Some(Self::Shift),
+            "SUPER" | "WIN" | "CMD" | "META" => Some(Self::Super),
+            _ => None,
+        }
+    }
+
+    fn modifiers(self) -> Modifiers {
+        match self {
+            Self::Alt => Modifiers::ALT,
+            Self::Ctrl => Modifiers::CONTROL,
+            Self::Shift => Modifiers::SHIFT,
+            Self::Super => Modifiers::SUPER,
+        }
+    }
+
+    fn from_rdev(key: rdev::Key) -> Option<Self> {
+        match key {
+            rdev::Key::Alt | rdev::Key::AltGr => Some(Self::Alt),
+            rdev::Key::ControlLeft | rdev::Key::ControlRight => Some(Self::Ctrl),
+            rdev::Key::ShiftLeft | rdev::Key::ShiftRight => Some(Self::Shift),
+            rdev::Key::MetaLeft | rdev::Key::MetaRight => Some(Self::Super),
+            _ => None,
+        }
+    }
+}
+
+/// 单独修饰键的触发方式
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ModifierTrigger {
+    /// 按住超过触发阈值（trigger_delay_ms）后触发，松开前只触发一次
+    Hold,
+    /// 在触发阈值内按下并松开时触发
+    Tap,
+    /// 在按键序列超时内连续轻按两次时触发（例如 "Shift Shift"）
+    DoubleTap,
+}
+
+/// 解析后的热键
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum HotkeyBinding {
+    /// 修饰键 + 普通按键，由系统全局热键注册
+    Combo(HotKey),
+    /// 单独的修饰键，由底层键盘监听识别（例如 "Alt"、"Ctrl:tap"、"Shift Shift"）
+    Modifier {
+        key: ModifierKey,
+        trigger: ModifierTrigger,
+    },
+    /// 依次按下的组合键（例如 "Ctrl+K Ctrl+O"），至少两步
+    Sequence(Vec<HotKey>),
+}
+
+impl HotkeyBinding {
+    /// 由系统全局热键识别的按键步骤，单独修饰键返回 None
+    fn combo_steps(&self) -> Option<&[HotKey]> {
+        match self {
+            Self::Combo(hotkey) => Some(std::slice::from_ref(hotkey)),
+            Self::Sequence(steps) => Some(steps),
+            Self::Modifier { .. } => None,
+        }
+    }
+
+    /// 两个绑定是否冲突：相同，或者一个是另一个按键序列的前缀
+    fn conflicts_with(&self, other: &HotkeyBinding) -> bool {
+        match (self.combo_steps(), other.combo_steps()) {
+            (Some(a), Some(b)) => a.starts_with(b) || b.starts_with(a),
+            _ => self == other,
+        }
+    }
+}
+
+fn format_combo(f: &mut fmt::Formatter<'_>, hotkey: &HotKey) -> fmt::Result {
+    for modifier in ModifierKey::ALL {
+        if hotkey.mods.contains(modifier.modifiers()) {
+            write!(f, "{}+", modifier.name())?;
+        }
+    }
+    match key_name(hotkey.key) {
+        Some(name) => f.write_str(name),
+        None => write!(f, "{:?}", hotkey.key),
+    }
+}
+
+/// 规范格式，例如 "Ctrl+Shift+A"、"Alt"、"Alt:tap"、"Ctrl+K Ctrl+O"，可以被 `parse_hotkey` 原样解析
+impl fmt::Display for HotkeyBinding {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Combo(hotkey) => format_combo(f, hotkey),
+            Self::Modifier { key, trigger: ModifierTrigger::Hold } => f.write_str(key.name()),
+            Self::Modifier { key, trigger: ModifierTrigger::Tap } => write!(f, "{}:tap", key.name()),
+            Self::Modifier { key, trigger: ModifierTrigger::DoubleTap } => {
+                write!(f, "{} {}", key.name(), key.name())
+            }
+            Self::Sequence(steps) => {
+                for (i, step) in steps.iter().enumerate() {
+                    if i > 0 {
+                        f.write_str(" ")?;
+                    }
+                    format_combo(f, step)?;
+                }
+                Ok(())
+            }
+        }
+    }
+}
+
+/// 热键触发事件（"hotkey-triggered" 的负载）
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct HotkeyTrigger {
+    pub action: HotkeyAction,
+    /// 按住修饰键时已经等待过 trigger_delay_ms，识别前无需再次延时
+    pub delay_elapsed: bool,
+}
+
+/// 键盘监听器的输入事件
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum InputEvent {
+    ModifierDown(ModifierKey),
+    ModifierUp(ModifierKey),
+    /// 任意非修饰键被按下
+    OtherKey,
+    /// 鼠标按键被按下
+    MouseButton,
+}
+
+impl InputEvent {
+    fn from_rdev(event: &rdev::EventType) -> Option<Self> {
+        match event {
+            rdev::EventType::KeyPress(key) => Some(
+                ModifierKey::from_rdev(*key)
+                    .map(Self::ModifierDown)
+                    .unwrap_or(Self::OtherKey),
+            ),
+            rdev::EventType::KeyRelease(key) => ModifierKey::from_rdev(*key).map(Self::ModifierUp),
+            rdev::EventType::ButtonPress(_) => Some(Self::MouseButton),
+            _ => None,
+        }
+    }
+}
+
+/// 单独修饰键的状态机
+///
+/// 按下期间出现其他按键、鼠标点击或其他修饰键时视为组合键，不会触发。
+#[derive(Debug)]
+pub struct ModifierTracker {
+    key: ModifierKey,
+    trigger: ModifierTrigger,
+    threshold: Duration,
+    /// 双击时两次轻按的最大间隔
+    sequence_timeout: Duration,
+    pressed_at: Option<Instant>,
+    /// 上一次有效轻按的时间（仅 DoubleTap）
+    last_tap: Option<Instant>,
+    /// 当前按住的其他修饰键
+    other_modifiers: Vec<ModifierKey>,
+    interrupted: bool,
+    fired: bool,
+}
+
+impl ModifierTracker {
+    pub fn new(key: ModifierKey, trigger: ModifierTrigger, threshold: Duration, sequence_timeout: Duration) -> Self {
+        Self {
+            key,
+            trigger,
+            threshold,
+            sequence_timeout,
+            pressed_at: None,
+            last_tap: None,
+            other_modifiers: Vec::new(),
+            interrupted: false,
+            fired: false,
+        }
+    }
+
+    /// 处理一个输入事件，返回是否触发
+    pub fn handle(&mut self, event: InputEvent, now: Instant) -> bool {
+        match event {
+            InputEvent::ModifierDown(key) if key == self.key => {
+                // 按住时系统会重复发送按下事件
+                if self.pressed_at.is_none() {
+                    self.pressed_at = Some(now);
+                    self.interrupted = !self.other_modifiers.is_empty();
+                    self.fired = false;
+                }
+                false
+            }
+            InputEvent::ModifierUp(key) if key == self.key => {
+                let is_tap = match self.pressed_at.take() {
+                    Some(pressed_at) => !self.interrupted && now.duration_since(pressed_at) < self.threshold,
+                    None => false,
+                };
+                match self.trigger {
+                    ModifierTrigger::Hold => false,
+                    ModifierTrigger::Tap => is_tap,
+                    ModifierTrigger::DoubleTap if !is_tap => {
+                        self.last_tap = None;
+                        false
+                    }
+                    ModifierTrigger::DoubleTap => match self.last_tap.take() {
+                        Some(last) if now.duration_since(last) <= self.sequence_timeout => true,
+                        _ => {
+                            self.last_tap = Some(now);
+                            false
+                        }
+                    },
+                }
+            }
+            InputEvent::ModifierDown(key) => {
+                if !self.other_modifiers.contains(&key) {
+                    self.other_modifiers.push(key);
+                }
+                self.interrupt();
+                false
+            }
+            InputEvent::ModifierUp(key) => {
+                self.other_modifiers.retain(|k| *k != key);
+                false
+            }
+            InputEvent::OtherKey | InputEvent::MouseButton => {
+                self.interrupt();
+                false
+            }
+        }
+    }
+
+    /// 检查按住时长，返回是否触发（仅对 Hold 有效）
+    pub fn poll(&mut self, now: Instant) -> bool {
+        if self.trigger != ModifierTrigger::Hold || self.interrupted || self.fired {
+            return false;
+        }
+        match self.pressed_at {
+            Some(pressed_at) if now.duration_since(pressed_at) >= self.threshold => {
+                self.fired = true;
+                true
+            }
+            _ => false,
+        }
+    }
+
+    fn interrupt(&mut self) {
+        // 两次轻按之间按下了其他键，不再算作双击
+        self.last_tap = None;
+        if self.pressed_at.is_some() {
+            self.interrupted = true;
+        }
+    }
+}
+
+/// 热键注册错误
+#[derive(Debug, Clone, PartialEq, Error)]
+pub enum HotkeyError {
+    /// 热键字符串无法解析
+    #[error("热键 \"{hotkey}\" 无效: {reason}")]
+    InvalidSyntax { hotkey: String, reason: String },
+    /// 同一个热键绑定了多个动作
+    #[error("热键 \"{hotkey}\" 重复绑定: {first:?} 与 {second:?}")]
+    Duplicate {
+        hotkey: String,
+        first: HotkeyAction,
+        second: HotkeyAction,
+    },
+    /// 已被其他程序占用
+    #[error("热键 \"{hotkey}\" 已被其他程序占用")]
+    AlreadyTaken { hotkey: String },
+    /// 系统拒绝注册或注销
+    #[error("系统拒绝注册热键 \"{hotkey}\": {reason}")]
+    OsDenied { hotkey: String, reason: String },
+    /// 无法初始化系统热键管理器
+    #[error("初始化热键管理器失败: {reason}")]
+    Unavailable { reason: String },
+}
+
+impl HotkeyError {
+    pub fn kind(&self) -> &'static str {
+        match self {
+            Self::InvalidSyntax { .. } => "invalid_syntax",
+            Self::Duplicate { .. } => "duplicate",
+            Self::AlreadyTaken { .. } => "already_taken",
+            Self::OsDenied { .. } => "os_denied",
+            Self::Unavailable { .. } => "unavailable",
+        }
+    }
+
+    pub fn hotkey(&self) -> &str {
+        match self {
+            Self::InvalidSyntax { hotkey, .. }
+            | Self::Duplicate { hotkey, .. }
+            | Self::AlreadyTaken { hotkey }
+            | Self::OsDenied { hotkey, .. } => hotkey,
+            Self::Unavailable { .. } => "",
+        }
+    }
+
+    /// 根据系统返回的错误区分“被占用”和其他失败
+    ///
+    /// 各平台都只在错误信息里说明被占用（Windows 的 ERROR_HOTKEY_ALREADY_REGISTERED、
+    /// X11 的 BadAccess），因此按关键字判断。
+    fn from_register(hotkey: String, error: global_hotkey::Error) -> Self {
+        match error {
+            global_hotkey::Error::AlreadyRegistered(_) => Self::AlreadyTaken { hotkey },
+            global_hotkey::Error::FailedToRegister(reason) => {
+                let lower = reason.to_lowercase();
+                if ["already", "taken", "in use", "badaccess"].iter().any(|k| lower.contains(k)) {
+                    Self::AlreadyTaken { hotkey }
+                } else {
+                    Self::OsDenied { hotkey, reason }
+                }
+            }
+            other => Self::OsDenied {
+                hotkey,
+                reason: other.to_string(),
+            },
+        }
+    }
+}
+
+/// 系统热键注册接口，测试中可以替换为模拟实现
+pub trait HotkeyRegistrar: Send + Sync {
+    fn register(&self, hotkey: HotKey) -> Result<(), global_hotkey::Error>;
+    fn unregister(&self, hotkey: HotKey) -> Result<(), global_hotkey::Error>;
+}
+
+impl HotkeyRegistrar for GlobalHotKeyManager {
+    fn register(&self, hotkey: HotKey) -> Result<(), global_hotkey::Error> {
+        GlobalHotKeyManager::register(self, hotkey)
+    }
+
+    fn unregister(&self, hotkey: HotKey) -> Result<(), global_hotkey::Error> {
+        GlobalHotKeyManager::unregister(self, hotkey)
+    }
+}
 
 pub struct HotKeyHandler {
-    manager: Arc<GlobalHotKeyManager>,
-    current_hotkey: Option<HotKey>,
+    registrar: Arc<dyn HotkeyRegistrar>,
+    /// 当前生效的绑定
+    active: Vec<(HotkeyBinding, HotkeyAction)>,
+    /// 当前单独修饰键的判定阈值
+    threshold: Duration,
+    /// 当前按键序列的步骤间超时
+    sequence_timeout: Duration,
+    /// 已注册的系统热键 ID 到动作的映射，由监听线程读取
+    actions: Arc<Mutex<HashMap<u32, HotkeyAction>>>,
+    /// 按键序列状态机，由监听线程驱动
+    sequences: Arc<Mutex<SequenceMatcher>>,
+    /// 单独修饰键热键，由键盘监听线程读取
+    modifier_trackers: Arc<Mutex<Vec<(ModifierTracker, HotkeyAction)>>>,
 }
 
 impl HotKeyHandler {
-    pub fn new() -> Result<Self, String> {
-        let manager = GlobalHotKeyManager::new()
-            .map_err(|e| format!("初始化热键管理器失败: {}", e))?;
-        
-        Ok(Self {
-            manager: Arc::new(manager),
-            current_hotkey: None,
-        })
-    }
-    
-    /// 注册热键
-    pub fn register(&mut self, hotkey_str: &str) -> Result<(), String> {
+    pub fn new() -> Result<Self, HotkeyError> {
+        let manager = GlobalHotKeyManager::new().map_err(|e| HotkeyError::Unavailable {
+            reason: e.to_string(),
+        })?;
+
+        Ok(Self::with_registrar(Arc::new(manager)))
+    }
+
+    pub fn with_registrar(registrar: Arc<dyn HotkeyRegistrar>) -> Self {
+        Self {
+            registrar,
+            active: Vec::new(),
+            threshold: Duration::from_millis(MIN_MODIFIER_THRESHOLD_MS as u64),
+            sequence_timeout: Duration::ZERO,
+            actions: Arc::new(Mutex::new(HashMap::new())),
+            sequences: Arc::new(Mutex::new(SequenceMatcher::default())),
+            modifier_trackers: Arc::new(Mutex::new(Vec::new())),
+        }
+    }
+
+    /// 解析全部绑定，拒绝无效热键、重复绑定以及与按键序列前缀冲突的绑定
+    pub fn validate_bindings(
+        bindings: &[HotkeyBindingConfig],
+    ) -> Result<Vec<(HotkeyBinding, HotkeyAction)>, HotkeyError> {
+        let mut parsed: Vec<(HotkeyBinding, HotkeyAction)> = Vec::with_capacity(bindings.len());
+
+        for binding in bindings {
+            let hotkey = Self::parse_hotkey(&binding.hotkey).map_err(|reason| HotkeyError::InvalidSyntax {
+                hotkey: binding.hotkey.clone(),
+                reason,
+            })?;
+
+            if let Some((_, action)) = parsed.iter().find(|(existing, _)| existing.conflicts_with(&hotkey)) {
+                return Err(HotkeyError::Duplicate {
+                    hotkey: hotkey.to_string(),
+                    first: *action,
+                    second: binding.action,
+                });
+            }
+            parsed.push((hotkey, binding.action));
+        }
+
+        Ok(parsed)
+    }
+
+    /// 注册全部热键绑定
+    ///
+    /// 单独修饰键以 `trigger_delay_ms` 作为按住/轻按的判定阈值，按键序列和双击的
+    /// 相邻两步需要在 `sequence_timeout_ms` 内完成。任何一个热键注册失败时恢复原来的绑定。
+    pub fn register_all(
+        &mut self,
+        bindings: &[HotkeyBindingConfig],
+        trigger_delay_ms: u32,
+        sequence_timeout_ms: u32,
+    ) -> Result<(), HotkeyError> {
+        let parsed = Self::validate_bindings(bindings)?;
+        let threshold = Duration::from_millis(trigger_delay_ms.max(MIN_MODIFIER_THRESHOLD_MS) as u64);
+        let sequence_timeout = Duration::from_millis(sequence_timeout_ms as u64);
+
         // 先注销旧热键
-        if let Some(old_hotkey) = self.current_hotkey.take() {
-            let _ = self.manager.unregister(old_hotkey);
-        }
-        
-        // 解析并注册新热键
-        let hotkey = Self::parse_hotkey(hotkey_str)?;
-        
-        self.manager.register(hotkey)
-            .map_err(|e| format!("注册热键失败: {}", e))?;
-        
-        self.current_hotkey = Some(hotkey);
-        println!("✅ 热键已注册: {}", hotkey_str);
-        
+        let previous = std::mem::take(&mut self.active);
+        if let Err(e) = self.deactivate(&previous) {
+            eprintln!("⚠️  {}", e);
+        }
+
+        if let Err(e) = self.activate(&parsed, threshold, sequence_timeout) {
+            eprintln!("⚠️  {}，恢复原来的热键", e);
+            match self.activate(&previous, self.threshold, self.sequence_timeout) {
+                Ok(()) => self.active = previous,
+                Err(rollback) => eprintln!("❌ 恢复原来的热键失败: {}", rollback),
+            }
+            return Err(e);
+        }
+
+        for (binding, action) in &parsed {
+            println!("✅ 热键已注册: {} → {:?}", binding, action);
+        }
+        self.active = parsed;
+        self.threshold = threshold;
+        self.sequence_timeout = sequence_timeout;
         Ok(())
     }
-    
-    /// 注销热键
-    pub fn unregister(&mut self) -> Result<(), String> {
-        if let Some(hotkey) = self.current_hotkey.take() {
-            self.manager.unregister(hotkey)
-                .map_err(|e| format!("注销热键失败: {}", e))?;
-            println!("🔓 热键已注销");
+
+    /// 检查热键能否用于 `action`：语法、与其他绑定是否重复、是否已被其他程序占用
+    ///
+    /// `editing` 是正在修改的绑定在 `bindings` 中的位置（新增绑定时为 None），检查时忽略它原来的热键。
+    /// 成功时返回规范格式的热键字符串。
+    pub fn check(
+        &self,
+        hotkey_str: &str,
+        action: HotkeyAction,
+        bindings: &[HotkeyBindingConfig],
+        editing: Option<usize>,
+    ) -> Result<String, HotkeyError> {
+        let binding = Self::parse_hotkey(hotkey_str).map_err(|reason| HotkeyError::InvalidSyntax {
+            hotkey: hotkey_str.to_string(),
+            reason,
+        })?;
+
+        // 与其他绑定冲突（忽略正在修改的这一项，同一动作的其他绑定也要检查）
+        for (i, other) in bindings.iter().enumerate() {
+            if Some(i) == editing {
+                continue;
+            }
+            if Self::parse_hotkey(&other.hotkey).is_ok_and(|other| other.conflicts_with(&binding)) {
+                return Err(HotkeyError::Duplicate {
+                    hotkey: binding.to_string(),
+                    first: other.action,
+                    second: action,
+                });
+            }
+        }
+
+        // 试注册系统热键（按键序列的每一步都需要可用），本程序已注册的热键无需再试
+        let registered: Vec<HotKey> = self
+            .active
+            .iter()
+            .filter_map(|(active, _)| active.combo_steps())
+            .flat_map(|steps| steps.iter().copied())
+            .collect();
+        let mut tried: Vec<HotKey> = Vec::new();
+        for step in binding.combo_steps().unwrap_or_default() {
+            if registered.contains(step) || tried.contains(step) {
+                continue;
+            }
+            tried.push(*step);
+            self.registrar
+                .register(*step)
+                .map_err(|e| HotkeyError::from_register(binding.to_string(), e))?;
+            let _ = self.registrar.unregister(*step);
+        }
+
+        Ok(binding.to_string())
+    }
+
+    /// 注销全部热键
+    pub fn unregister(&mut self) -> Result<(), HotkeyError> {
+        let previous = std::mem::take(&mut self.active);
+        self.deactivate(&previous)?;
+        println!("🔓 热键已注销");
+        Ok(())
+    }
+
+    /// 注册一组绑定，失败时撤销本次已注册的部分
+    ///
+    /// 按键序列只常驻注册第一步，后续步骤在等待期间临时注册（见 `start_listener`）。
+    fn activate(
+        &mut self,
+        bindings: &[(HotkeyBinding, HotkeyAction)],
+        threshold: Duration,
+        sequence_timeout: Duration,
+    ) -> Result<(), HotkeyError> {
+        let mut actions = HashMap::new();
+        let mut trackers = Vec::new();
+        let mut sequences = Vec::new();
+        let mut combos = Vec::new();
+
+        for (binding, action) in bindings {
+            match binding {
+                HotkeyBinding::Combo(hotkey) => {
+                    combos.push((*hotkey, binding.to_string()));
+                    actions.insert(hotkey.id(), *action);
+                }
+                HotkeyBinding::Modifier { key, trigger } => {
+                    trackers.push((ModifierTracker::new(*key, *trigger, threshold, sequence_timeout), *action));
+                }
+                HotkeyBinding::Sequence(steps) => sequences.push((steps.clone(), *action)),
+            }
+        }
+        for step in SequenceMatcher::first_steps(&sequences) {
+            combos.push((step, HotkeyBinding::Combo(step).to_string()));
         }
+
+        let mut registered = Vec::new();
+        for (hotkey, name) in combos {
+            if let Err(e) = self.registrar.register(hotkey) {
+                for hotkey in registered {
+                    let _ = self.registrar.unregister(hotkey);
+                }
+                return Err(HotkeyError::from_register(name, e));
+            }
+            registered.push(hotkey);
+        }
+
+        let permanent = registered.iter().map(|hotkey| hotkey.id()).collect();
+        *self.actions.lock().unwrap() = actions;
+        *self.modifier_trackers.lock().unwrap() = trackers;
+        *self.sequences.lock().unwrap() = SequenceMatcher::new(sequences, sequence_timeout, permanent);
         Ok(())
     }
-    
-    /// 解析热键字符串 (例如: "Alt", "Ctrl+Shift+A", "Alt+F1")
-    fn parse_hotkey(hotkey_str: &str) -> Result<HotKey, String> {
-        let parts: Vec<&str> = hotkey_str.split('+').map(|s| s.trim()).collect();
-        
-        if parts.is_empty() {
+
+    /// 注销一组绑定
+    fn deactivate(&mut self, bindings: &[(HotkeyBinding, HotkeyAction)]) -> Result<(), HotkeyError> {
+        self.actions.lock().unwrap().clear();
+        self.modifier_trackers.lock().unwrap().clear();
+        *self.sequences.lock().unwrap() = SequenceMatcher::default();
+
+        let mut hotkeys: Vec<HotKey> = Vec::new();
+        for (binding, _) in bindings {
+            match binding {
+                HotkeyBinding::Combo(hotkey) => hotkeys.push(*hotkey),
+                HotkeyBinding::Sequence(steps) if !hotkeys.contains(&steps[0]) => hotkeys.push(steps[0]),
+                _ => {}
+            }
+        }
+
+        let mut result = Ok(());
+        for hotkey in hotkeys {
+            if let Err(e) = self.registrar.unregister(hotkey) {
+                result = Err(HotkeyError::OsDenied {
+                    hotkey: HotkeyBinding::Combo(hotkey).to_string(),
+                    reason: e.to_string(),
+                });
+            }
+        }
+        result
+    }
+
+    /// 解析热键字符串 (例如: "Alt", "Alt:tap", "Ctrl+Shift+A", "Alt+F1", "Ctrl+K Ctrl+O", "Shift Shift")
+    pub fn parse_hotkey(hotkey_str: &str) -> Result<HotkeyBinding, String> {
+        // 去掉 "+" 两侧的空格，剩下的空白用于分隔序列的各步
+        let compact = hotkey_str.split('+').map(str::trim).collect::<Vec<_>>().join("+");
+        let steps: Vec<&str> = compact.split_whitespace().collect();
+
+        match steps.as_slice() {
+            [] => Err("热键不能为空".to_string()),
+            [step] => Self::parse_step(step),
+            [first, second] if first.eq_ignore_ascii_case(second) && ModifierKey::parse(first).is_some() => {
+                Ok(HotkeyBinding::Modifier {
+                    key: ModifierKey::parse(first).unwrap_or(ModifierKey::Alt),
+                    trigger: ModifierTrigger::DoubleTap,
+                })
+            }
+            _ => {
+                let combos = steps
+                    .iter()
+                    .map(|step| match Self::parse_step(step)? {
+                        HotkeyBinding::Combo(hotkey) => Ok(hotkey),
+                        _ => Err(format!(
+                            "按键序列只支持组合键，或同一修饰键双击（如 \"Shift Shift\"）: {}",
+                            step
+                        )),
+                    })
+                    .collect::<Result<Vec<_>, _>>()?;
+                Ok(HotkeyBinding::Sequence(combos))
+            }
+        }
+    }
+
+    /// 解析序列中的一步
+    fn parse_step(step: &str) -> Result<HotkeyBinding, String> {
+        let (keys, trigger) = match step.rsplit_once(':') {
+            Some((keys, trigger)) => match trigger.trim().to_uppercase().as_str() {
+                "HOLD" => (keys, Some(ModifierTrigger::Hold)),
+                "TAP" => (keys, Some(ModifierTrigger::Tap)),
+                _ => return Err(format!("不支持的触发方式: {}", trigger.trim())),
+            },
+            None => (step, None),
+        };
+
+        let parts: Vec<&str> = keys.split('+').map(|s| s.trim()).collect();
+
+        if parts.iter().all(|s| s.is_empty()) {
             return Err("热键不能为空".to_string());
         }
-        
+
+        // 单独的修饰键
+        if let [part] = parts.as_slice() {
+            if let Some(key) = ModifierKey::parse(part) {
+                return Ok(HotkeyBinding::Modifier {
+                    key,
+                    trigger: trigger.unwrap_or(ModifierTrigger::Hold),
+                });
+            }
+        }
+
+        if trigger.is_some() {
+            return Err("只有单独的修饰键支持 :hold / :tap".to_string());
+        }
+
         let mut modifiers = Modifiers::empty();
         let mut key_code: Option<Code> = None;
-        
+
         for part in parts {
-            match part.to_uppercase().as_str() {
-                "CTRL" | "CONTROL" => modifiers |= Modifiers::CONTROL,
-                "ALT" => modifiers |= Modifiers::ALT,
-                "SHIFT" => modifiers |= Modifiers::SHIFT,
-                "SUPER" | "WIN" | "CMD" => modifiers |= Modifiers::SUPER,
-                // 字母键
-                "A" => key_code = Some(Code::KeyA),
-                "B" => key_code = Some(Code::KeyB),
-                "C" => key_code = Some(Code::KeyC),
-                "D" => key_code = Some(Code::KeyD),
-                "E" => key_code = Some(Code::KeyE),
-                "F" => key_code = Some(Code::KeyF),
-                "G" => key_code = Some(Code::KeyG),
-                "H" => key_code = Some(Code::KeyH),
-                "I" => key_code = Some(Code::KeyI),
-                "J" => key_code = Some(Code::KeyJ),
-                "K" => key_code = Some(Code::KeyK),
-                "L" => key_code = Some(Code::KeyL),
-                "M" => key_code = Some(Code::KeyM),
-                "N" => key_code = Some(Code::KeyN),
-                "O" => key_code = Some(Code::KeyO),
-                "P" => key_code = Some(Code::KeyP),
-                "Q" => key_code = Some(Code::KeyQ),
-                "R" => key_code = Some(Code::KeyR),
-                "S" => key_code = Some(Code::KeyS),
-                "T" => key_code = Some(Code::KeyT),
-                "U" => key_code = Some(Code::KeyU),
-                "V" => key_code = Some(Code::KeyV),
-                "W" => key_code = Some(Code::KeyW),
-                "X" => key_code = Some(Code::KeyX),
-                "Y" => key_code = Some(Code::KeyY),
-                "Z" => key_code = Some(Code::KeyZ),
-                // 功能键
-                "F1" => key_code = Some(Code::F1),
-                "F2" => key_code = Some(Code::F2),
-                "F3" => key_code = Some(Code::F3),
-                "F4" => key_code = Some(Code::F4),
-                "F5" => key_code = Some(Code::F5),
-                "F6" => key_code = Some(Code::F6),
-                "F7" => key_code = Some(Code::F7),
-                "F8" => key_code = Some(Code::F8),
-                "F9" => key_code = Some(Code::F9),
-                "F10" => key_code = Some(Code::F10),
-                "F11" => key_code = Some(Code::F11),
-                "F12" => key_code = Some(Code::F12),
-                // 数字键
-                "0" => key_code = Some(Code::Digit0),
-                "1" => key_code = Some(Code::Digit1),
-                "2" => key_code = Some(Code::Digit2),
-                "3" => key_code = Some(Code::Digit3),
-                "4" => key_code = Some(Code::Digit4),
-                "5" => key_code = Some(Code::Digit5),
-                "6" => key_code = Some(Code::Digit6),
-                "7" => key_code = Some(Code::Digit7),
-                "8" => key_code = Some(Code::Digit8),
-                "9" => key_code = Some(Code::Digit9),
-                // 特殊键
-                "SPACE" => key_code = Some(Code::Space),
-                "ENTER" => key_code = Some(Code::Enter),
-                "TAB" => key_code = Some(Code::Tab),
-                "BACKSPACE" => key_code = Some(Code::Backspace),
-                "ESC" | "ESCAPE" => key_code = Some(Code::Escape),
-                _ => return Err(format!("不支持的按键: {}", part)),
-            }
-        }
-        
+            if let Some(key) = ModifierKey::parse(part) {
+                modifiers |= key.modifiers();
+                continue;
+            }
+            let code = keys::parse_key(part).ok_or_else(|| format!("不支持的按键: {}", part))?;
+            if key_code.replace(code).is_some() {
+                return Err("只能指定一个非修饰键".to_string());
+            }
+        }
+
         let code = key_code.ok_or_else(|| "未指定按键".to_string())?;
-        
-        Ok(HotKey::new(Some(modifiers), code))
+
+        Ok(HotkeyBinding::Combo(HotKey::new(Some(modifiers), code)))
     }
-    
+
+    /// 把热键字符串转换为规范格式（例如 "ctrl+shift+a" → "Ctrl+Shift+A"）
+    pub fn normalize_hotkey(hotkey_str: &str) -> Result<String, String> {
+        Ok(Self::parse_hotkey(hotkey_str)?.to_string())
+    }
+
     /// 启动热键事件监听
-    pub fn start_listener(app: AppHandle) {
+    pub fn start_listener(&self, app: AppHandle) {
+        let app_clone = app.clone();
+        let actions = self.actions.clone();
+        let sequences = self.sequences.clone();
+        let registrar = self.registrar.clone();
         std::thread::spawn(move || {
             let receiver = GlobalHotKeyEvent::receiver();
+            // 等待序列下一步时临时注册的热键
+            let temporary: Arc<Mutex<Vec<HotKey>>> = Arc::default();
+            // 最近一次请求主线程注册的临时热键
+            let mut requested: Vec<HotKey> = Vec::new();
             loop {
-                if let Ok(event) = receiver.recv() {
-                    println!("🔥 热键触发: {:?}", event);
-                    
-                    // 触发 OCR
-                    let app_clone = app.clone();
-                    tauri::async_runtime::spawn(async move {
-                        if let Err(e) = app_clone.emit_all("hotkey-triggered", ()) {
-                            eprintln!("❌ 发送热键事件失败: {}", e);
-                        }
+                let event = match receiver.recv_timeout(SEQUENCE_POLL_INTERVAL) {
+                    Ok(event) => Some(event),
+                    Err(e) if e.is_disconnected() => break,
+                    Err(_) => None,
+                };
+
+                let (action, wanted) = dispatch_event(event, Instant::now(), &actions, &sequences);
+
+                // Windows 只能在创建 GlobalHotKeyManager 的线程上注册热键（macOS 要求主线程），
+                // 管理器在主线程创建，所以由主线程同步临时注册的热键
+                if wanted != requested {
+                    requested = wanted.clone();
+                    let (registrar, temporary) = (registrar.clone(), temporary.clone());
+                    let result = app_clone.run_on_main_thread(move || {
+                        sync_temporary_keys(registrar.as_ref(), &temporary, &wanted);
                     });
+                    if let Err(e) = result {
+                        eprintln!("⚠️  临时注册热键失败: {}", e);
+                    }
+                }
+
+                if let Some(action) = action {
+                    println!("🔥 热键触发: {:?}", action);
+                    emit_trigger(&app_clone, HotkeyTrigger { action, delay_elapsed: false });
                 }
             }
         });
+
+        Self::start_modifier_listener(app, self.modifier_trackers.clone());
+    }
+
+    /// 启动底层键盘监听，识别单独修饰键的按住/轻按
+    fn start_modifier_listener(app: AppHandle, trackers: Arc<Mutex<Vec<(ModifierTracker, HotkeyAction)>>>) {
+        let (tx, rx) = mpsc::channel();
+
+        std::thread::spawn(move || {
+            let result = rdev::listen(move |event| {
+                if let Some(input) = InputEvent::from_rdev(&event.event_type) {
+                    let _ = tx.send(input);
+                }
+            });
+            if let Err(e) = result {
+                eprintln!("⚠️  键盘监听启动失败，单独修饰键热键不可用: {:?}", e);
+            }
+        });
+
+        std::thread::spawn(move || loop {
+            let input = match rx.recv_timeout(MODIFIER_POLL_INTERVAL) {
+                Ok(input) => Some(input),
+                Err(RecvTimeoutError::Timeout) => None,
+                Err(RecvTimeoutError::Disconnected) => break,
+            };
+
+            let triggers: Vec<HotkeyTrigger> = {
+                let mut trackers = trackers.lock().unwrap();
+                let now = Instant::now();
+                trackers
+                    .iter_mut()
+                    .filter_map(|(tracker, action)| {
+                        let fired = input.is_some_and(|input| tracker.handle(input, now));
+                        if fired {
+                            Some(HotkeyTrigger { action: *action, delay_elapsed: false })
+                        } else if tracker.poll(now) {
+                            Some(HotkeyTrigger { action: *action, delay_elapsed: true })
+                        } else {
+                            None
+                        }
+                    })
+                    .collect()
+            };
+
+            for trigger in triggers {
+                println!("🔥 修饰键热键触发: {:?}", trigger);
+                emit_trigger(&app, trigger);
+            }
+        });
     }
 }
 
+/// 处理一个系统热键事件（没有事件时只检查序列超时），返回触发的动作和等待序列下一步时需要临时注册的热键
+///
+/// 系统在按下和松开时各发送一次事件，只处理按下事件，否则每次按键会触发两次。
+fn dispatch_event(
+    event: Option<GlobalHotKeyEvent>,
+    now: Instant,
+    actions: &Mutex<HashMap<u32, HotkeyAction>>,
+    sequences: &Mutex<SequenceMatcher>,
+) -> (Option<HotkeyAction>, Vec<HotKey>) {
+    let mut matcher = sequences.lock().unwrap();
+    let action = match event {
+        Some(event) if event.state != HotKeyState::Pressed => {
+            matcher.poll(now);
+            None
+        }
+        Some(event) => match matcher.handle(event.id, now) {
+            SequenceOutcome::Fired(action) => Some(action),
+            SequenceOutcome::Pending => None,
+            SequenceOutcome::Unmatched => {
+                let action = actions.lock().unwrap().get(&event.id).copied();
+                if action.is_none() {
+                    println!("⚠️  未知的热键事件: {:?}", event);
+                }
+                action
+            }
+        },
+        None => {
+            matcher.poll(now);
+            None
+        }
+    };
+    (action, matcher.temporary_keys())
+}
+
+/// 注册 `wanted` 中尚未注册的临时热键，注销不再需要的
+fn sync_temporary_keys(registrar: &dyn HotkeyRegistrar, temporary: &Mutex<Vec<HotKey>>, wanted: &[HotKey]) {
+    let mut temporary = temporary.lock().unwrap();
+    temporary.retain(|hotkey| {
+        if wanted.contains(hotkey) {
+            return true;
+        }
+        let _ = registrar.unregister(*hotkey);
+        false
+    });
+    for hotkey in wanted {
+        if !temporary.contains(hotkey) {
+            match registrar.register(*hotkey) {
+                Ok(()) => temporary.push(*hotkey),
+                Err(e) => eprintln!("⚠️  临时注册热键失败: {}", e),
+            }
+        }
+    }
+}
+
+/// 通知前端和主进程热键已触发
+fn emit_trigger(app: &AppHandle, trigger: HotkeyTrigger) {
+    let app = app.clone();
+    tauri::async_runtime::spawn(async move {
+        if let Err(e) = app.emit_all("hotkey-triggered", trigger) {
+            eprintln!("❌ 发送热键事件失败: {}", e);
+        }
+    });
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
-    
+    use crate::error::AppError;
+
+    /// 模拟系统热键注册，`taken` 中的热键视为被其他程序占用
+    #[derive(Default)]
+    struct FakeRegistrar {
+        taken: Vec<u32>,
+        registered: Mutex<Vec<u32>>,
+    }
+
+    impl HotkeyRegistrar for FakeRegistrar {
+        fn register(&self, hotkey: HotKey) -> Result<(), global_hotkey::Error> {
+            if self.taken.contains(&hotkey.id()) {
+                return Err(global_hotkey::Error::FailedToRegister(
+                    "hotkey already registered by another application".to_string(),
+                ));
+            }
+            self.registered.lock().unwrap().push(hotkey.id());
+            Ok(())
+        }
+
+        fn unregister(&self, hotkey: HotKey) -> Result<(), global_hotkey::Error> {
+            self.registered.lock().unwrap().retain(|id| *id != hotkey.id());
+            Ok(())
+        }
+    }
+
+    fn hotkey_id(hotkey: &str) -> u32 {
+        match HotKeyHandler::parse_hotkey(hotkey).unwrap() {
+            HotkeyBinding::Combo(hotkey) => hotkey.id(),
+            _ => panic!("不是组合键"),
+        }
+    }
+
+    fn tracker(trigger: ModifierTrigger) -> ModifierTracker {
+        ModifierTracker::new(ModifierKey::Alt, trigger, Duration::from_millis(300), Duration::from_millis(800))
+    }
+
+    fn ms(start: Instant, millis: u64) -> Instant {
+        start + Duration::from_millis(millis)
+    }
+
     #[test]
     fn test_parse_simple_key() {
-        let hotkey = HotKeyHandler::parse_hotkey("Alt").unwrap();
-        assert!(hotkey.mods.contains(Modifiers::ALT));
+        let binding = HotKeyHandler::parse_hotkey("Alt").unwrap();
+        assert_eq!(
+            binding,
+            HotkeyBinding::Modifier { key: ModifierKey::Alt, trigger: ModifierTrigger::Hold }
+        );
+
+        let binding = HotKeyHandler::parse_hotkey("ctrl:tap").unwrap();
+        assert_eq!(
+            binding,
+            HotkeyBinding::Modifier { key: ModifierKey::Ctrl, trigger: ModifierTrigger::Tap }
+        );
     }
-    
+
     #[test]
     fn test_parse_combination() {
-        let hotkey = HotKeyHandler::parse_hotkey("Ctrl+Shift+A").unwrap();
+        let HotkeyBinding::Combo(hotkey) = HotKeyHandler::parse_hotkey("Ctrl+Shift+A").unwrap() else {
+            panic!("应解析为组合键");
+        };
         assert!(hotkey.mods.contains(Modifiers::CONTROL));
         assert!(hotkey.mods.contains(Modifiers::SHIFT));
     }
-    
+
     #[test]
     fn test_parse_invalid() {
         let result = HotKeyHandler::parse_hotkey("InvalidKey");
         assert!(result.is_err());
+        assert!(HotKeyHandler::parse_hotkey("Ctrl+A:tap").is_err());
+        assert!(HotKeyHandler::parse_hotkey("Ctrl+Alt").is_err());
+        assert!(HotKeyHandler::parse_hotkey("Alt:press").is_err());
     }
-}
 
+    #[test]
+    fn test_format_round_trip() {
+        // 所有修饰键组合
+        let modifier_sets: Vec<Modifiers> = (0..16u8)
+            .map(|bits| {
+                ModifierKey::ALL
+                    .iter()
+                    .enumerate()
+                    .filter(|(i, _)| bits & (1 << i) != 0)
+                    .fold(Modifiers::empty(), |mods, (_, key)| mods | key.modifiers())
+            })
+            .collect();
+
+        for code in supported_keys() {
+            for mods in &modifier_sets {
+                let binding = HotkeyBinding::Combo(HotKey::new(Some(*mods), code));
+                let formatted = binding.to_string();
+                assert_eq!(HotKeyHandler::parse_hotkey(&formatted), Ok(binding), "{}", formatted);
+            }
+        }
+
+        for key in ModifierKey::ALL {
+            for trigger in [ModifierTrigger::Hold, ModifierTrigger::Tap, ModifierTrigger::DoubleTap] {
+                let binding = HotkeyBinding::Modifier { key, trigger };
+                assert_eq!(HotKeyHandler::parse_hotkey(&binding.to_string()), Ok(binding));
+            }
+        }
+    }
+
+    #[test]
+    fn test_normalize_hotkey() {
+        assert_eq!(HotKeyHandler::normalize_hotkey("ctrl+shift+a").unwrap(), "Ctrl+Shift+A");
+        assert_eq!(HotKeyHandler::normalize_hotkey("shift+CONTROL+pgdn").unwrap(), "Ctrl+Shift+PageDown");
+        assert_eq!(HotKeyHandler::normalize_hotkey("Super+Alt+KeyP").unwrap(), "Alt+Super+P");
+        assert_eq!(HotKeyHandler::normalize_hotkey("alt:HOLD").unwrap(), "Alt");
+        assert_eq!(HotKeyHandler::normalize_hotkey("meta:tap").unwrap(), "Super:tap");
+        assert!(HotKeyHandler::normalize_hotkey("Ctrl+A+B").is_err());
+    }
+
+    #[test]
+    fn test_parse_sequence() {
+        let HotkeyBinding::Sequence(steps) = HotKeyHandler::parse_hotkey("ctrl+k  ctrl + o").unwrap() else {
+            panic!("应解析为按键序列");
+        };
+        assert_eq!(steps.len(), 2);
+        assert_eq!(HotkeyBinding::Sequence(steps).to_string(), "Ctrl+K Ctrl+O");
+
+        assert_eq!(
+            HotKeyHandler::parse_hotkey("shift shift").unwrap(),
+            HotkeyBinding::Modifier { key: ModifierKey::Shift, trigger: ModifierTrigger::DoubleTap }
+        );
+        assert_eq!(HotKeyHandler::normalize_hotkey("Ctrl + K").unwrap(), "Ctrl+K");
+
+        // 序列中不能混入单独修饰键
+        assert!(HotKeyHandler::parse_hotkey("Ctrl+K Alt").is_err());
+        assert!(HotKeyHandler::parse_hotkey("Shift Alt").is_err());
+    }
+
+    fn binding(hotkey: &str, action: HotkeyAction) -> HotkeyBindingConfig {
+        HotkeyBindingConfig { hotkey: hotkey.to_string(), action }
+    }
+
+    #[test]
+    fn test_validate_bindings() {
+        let bindings = vec![
+            binding("Alt", HotkeyAction::FullScreenOcr),
+            binding("Alt:tap", HotkeyAction::RegionSelect),
+            binding("Ctrl+Shift+R", HotkeyAction::RepeatLastRegion),
+        ];
+        let parsed = HotKeyHandler::validate_bindings(&bindings).unwrap();
+        assert_eq!(parsed.len(), 3);
+        assert_eq!(parsed[2].1, HotkeyAction::RepeatLastRegion);
+
+        // 大小写与顺序不同但实际相同的组合键
+        let duplicated = vec![
+            binding("Ctrl+Shift+R", HotkeyAction::RepeatLastRegion),
+            binding("shift+ctrl+r", HotkeyAction::ShowHistory),
+        ];
+        assert!(HotKeyHandler::validate_bindings(&duplicated).is_err());
+
+        let duplicated = vec![
+            binding("Alt", HotkeyAction::FullScreenOcr),
+            binding("alt:hold", HotkeyAction::ToggleLiveMode),
+        ];
+        assert!(HotKeyHandler::validate_bindings(&duplicated).is_err());
+
+        // 组合键是按键序列的前缀时，序列永远无法触发
+        let prefix = vec![
+            binding("Ctrl+K", HotkeyAction::FullScreenOcr),
+            binding("Ctrl+K Ctrl+O", HotkeyAction::ShowHistory),
+        ];
+        assert_eq!(HotKeyHandler::validate_bindings(&prefix).unwrap_err().kind(), "duplicate");
+
+        let chords = vec![
+            binding("Ctrl+K Ctrl+O", HotkeyAction::FullScreenOcr),
+            binding("Ctrl+K Ctrl+H", HotkeyAction::ShowHistory),
+        ];
+        assert!(HotKeyHandler::validate_bindings(&chords).is_ok());
+
+        let invalid = vec![binding("Ctrl+Nope", HotkeyAction::ShowHistory)];
+        let error = HotKeyHandler::validate_bindings(&invalid).unwrap_err();
+        assert_eq!(error.kind(), "invalid_syntax");
+    }
+
+    #[test]
+    fn test_failed_registration_rolls_back() {
+        let registrar = Arc::new(FakeRegistrar {
+            taken: vec![hotkey_id("Ctrl+Shift+T")],
+            ..Default::default()
+        });
+        let mut handler = HotKeyHandler::with_registrar(registrar.clone());
+
+        let original = vec![
+            binding("Ctrl+Shift+O", HotkeyAction::FullScreenOcr),
+            binding("Ctrl+Shift+H", HotkeyAction::ShowHistory),
+        ];
+        handler.register_all(&original, 300, 800).unwrap();
+
+        let updated = vec![
+            binding("Ctrl+Shift+P", HotkeyAction::FullScreenOcr),
+            binding("Ctrl+Shift+T", HotkeyAction::ShowHistory),
+        ];
+        let error = handler.register_all(&updated, 300, 800).unwrap_err();
+        assert_eq!(error, HotkeyError::AlreadyTaken { hotkey: "Ctrl+Shift+T".to_string() });
+
+        // 原来的热键仍然有效，新热键没有残留
+        let mut registered = registrar.registered.lock().unwrap().clone();
+        registered.sort();
+        let mut expected = vec![hotkey_id("Ctrl+Shift+O"), hotkey_id("Ctrl+Shift+H")];
+        expected.sort();
+        assert_eq!(registered, expected);
+        assert_eq!(
+            handler.actions.lock().unwrap().get(&hotkey_id("Ctrl+Shift+O")),
+            Some(&HotkeyAction::FullScreenOcr)
+        );
+    }
+
+    #[test]
+    fn test_check_hotkey() {
+        let registrar = Arc::new(FakeRegistrar {
+            taken: vec![hotkey_id("Ctrl+Alt+Delete")],
+            ..Default::default()
+        });
+        let mut handler = HotKeyHandler::with_registrar(registrar.clone());
+        let bindings = vec![
+            binding("Alt", HotkeyAction::FullScreenOcr),
+            binding("Ctrl+Shift+H", HotkeyAction::ShowHistory),
+        ];
+        handler.register_all(&bindings, 300, 800).unwrap();
+
+        assert_eq!(
+            handler.check("ctrl+shift+o", HotkeyAction::FullScreenOcr, &bindings, Some(0)).unwrap(),
+            "Ctrl+Shift+O"
+        );
+        // 修改的绑定原来的热键不算冲突
+        assert!(handler.check("Ctrl+Shift+H", HotkeyAction::ShowHistory, &bindings, Some(1)).is_ok());
+        // 同一动作新增的第二个绑定仍然不能与第一个重复
+        assert_eq!(
+            handler.check("Ctrl+Shift+H", HotkeyAction::ShowHistory, &bindings, None).unwrap_err().kind(),
+            "duplicate"
+        );
+
+        let kind = |hotkey: &str| {
+            handler
+                .check(hotkey, HotkeyAction::FullScreenOcr, &bindings, Some(0))
+                .unwrap_err()
+                .kind()
+        };
+        assert_eq!(kind("Ctrl+Shift+H"), "duplicate");
+        assert_eq!(kind("Ctrl+Alt+Delete"), "already_taken");
+        assert_eq!(kind("Ctrl+"), "invalid_syntax");
+
+        // 检查不会留下注册
+        assert_eq!(registrar.registered.lock().unwrap().len(), 1);
+    }
+
+    #[test]
+    fn test_error_serialization() {
+        let error = HotkeyError::from_register(
+            "Ctrl+A".to_string(),
+            global_hotkey::Error::FailedToRegister("permission denied".to_string()),
+        );
+        let json = serde_json::to_value(AppError::from(error)).unwrap();
+        assert_eq!(json["code"], "hotkey_unavailable");
+        assert_eq!(json["details"]["kind"], "os_denied");
+        assert_eq!(json["details"]["hotkey"], "Ctrl+A");
+        assert!(json["message"].as_str().unwrap().contains("permission denied"));
+    }
+
+    #[test]
+    fn test_hold_fires_once_after_threshold() {
+        let start = Instant::now();
+        let mut tracker = tracker(ModifierTrigger::Hold);
+
+        assert!(!tracker.handle(InputEvent::ModifierDown(ModifierKey::Alt), start));
+        assert!(!tracker.poll(ms(start, 299)));
+        // 按键自动重复不会重置计时
+        assert!(!tracker.handle(InputEvent::ModifierDown(ModifierKey::Alt), ms(start, 200)));
+        assert!(tracker.poll(ms(start, 300)));
+        assert!(!tracker.poll(ms(start, 400)));
+        assert!(!tracker.handle(InputEvent::ModifierUp(ModifierKey::Alt), ms(start, 500)));
+
+        // 再次按住可以再次触发
+        tracker.handle(InputEvent::ModifierDown(ModifierKey::Alt), ms(start, 1000));
+        assert!(tracker.poll(ms(start, 1300)));
+    }
+
+    #[test]
+    fn test_hold_interrupted_by_combination() {
+        let start = Instant::now();
+        let mut tracker = tracker(ModifierTrigger::Hold);
+
+        // Alt+Tab
+        tracker.handle(InputEvent::ModifierDown(ModifierKey::Alt), start);
+        tracker.handle(InputEvent::OtherKey, ms(start, 50));
+        assert!(!tracker.poll(ms(start, 500)));
+        tracker.handle(InputEvent::ModifierUp(ModifierKey::Alt), ms(start, 600));
+
+        // Ctrl 按住时再按 Alt
+        tracker.handle(InputEvent::ModifierDown(ModifierKey::Ctrl), ms(start, 1000));
+        tracker.handle(InputEvent::ModifierDown(ModifierKey::Alt), ms(start, 1010));
+        assert!(!tracker.poll(ms(start, 2000)));
+        tracker.handle(InputEvent::ModifierUp(ModifierKey::Alt), ms(start, 2010));
+        tracker.handle(InputEvent::ModifierUp(ModifierKey::Ctrl), ms(start, 2020));
+
+        // 松开其他修饰键后恢复正常
+        tracker.handle(InputEvent::ModifierDown(ModifierKey::Alt), ms(start, 3000));
+        assert!(tracker.poll(ms(start, 3300)));
+    }
+
+    #[test]
+    fn test_tap() {
+        let start = Instant::now();
+        let mut tracker = tracker(ModifierTrigger::Tap);
+
+        tracker.handle(InputEvent::ModifierDown(ModifierKey::Alt), start);
+        assert!(!tracker.poll(ms(start, 100)));
+        assert!(tracker.handle(InputEvent::ModifierUp(ModifierKey::Alt), ms(start, 100)));
+
+        // 按住过久
+        tracker.handle(InputEvent::ModifierDown(ModifierKey::Alt), ms(start, 1000));
+        assert!(!tracker.handle(InputEvent::ModifierUp(ModifierKey::Alt), ms(start, 1400)));
+
+        // 期间点击了鼠标（例如 Alt+拖动窗口）
+        tracker.handle(InputEvent::ModifierDown(ModifierKey::Alt), ms(start, 2000));
+        tracker.handle(InputEvent::MouseButton, ms(start, 2050));
+        assert!(!tracker.handle(InputEvent::ModifierUp(ModifierKey::Alt), ms(start, 2100)));
+    }
+
+    #[test]
+    fn test_double_tap() {
+        let start = Instant::now();
+        let mut tracker = tracker(ModifierTrigger::DoubleTap);
+
+        let tap = |tracker: &mut ModifierTracker, at: u64| {
+            tracker.handle(InputEvent::ModifierDown(ModifierKey::Alt), ms(start, at));
+            tracker.handle(InputEvent::ModifierUp(ModifierKey::Alt), ms(start, at + 50))
+        };
+        assert!(!tap(&mut tracker, 0));
+        assert!(tap(&mut tracker, 400));
+
+        // 两次轻按间隔超过 sequence_timeout
+        assert!(!tap(&mut tracker, 2000));
+        assert!(!tap(&mut tracker, 3000));
+        assert!(tap(&mut tracker, 3500));
+
+        // 中间的组合键会打断双击
+        assert!(!tap(&mut tracker, 5000));
+        tracker.handle(InputEvent::OtherKey, ms(start, 5100));
+        assert!(!tap(&mut tracker, 5200));
+    }
+
+    #[test]
+    fn test_press_and_release_fire_once() {
+        let mut handler = HotKeyHandler::with_registrar(Arc::new(FakeRegistrar::default()));
+        let bindings = vec![binding("Ctrl+Shift+L", HotkeyAction::ToggleLiveMode)];
+        handler.register_all(&bindings, 300, 800).unwrap();
+
+        let start = Instant::now();
+        let event = |state| Some(GlobalHotKeyEvent { id: hotkey_id("Ctrl+Shift+L"), state });
+        let fired: Vec<_> = [(HotKeyState::Pressed, 0), (HotKeyState::Released, 80)]
+            .into_iter()
+            .filter_map(|(state, at)| dispatch_event(event(state), ms(start, at), &handler.actions, &handler.sequences).0)
+            .collect();
+        assert_eq!(fired, [HotkeyAction::ToggleLiveMode]);
+    }
+
+    #[test]
+    fn test_sequence_counts_presses_only() {
+        let registrar = Arc::new(FakeRegistrar::default());
+        let mut handler = HotKeyHandler::with_registrar(registrar.clone());
+        let bindings = vec![
+            binding("Ctrl+C Ctrl+C", HotkeyAction::OcrClipboardImage),
+            binding("Ctrl+K Ctrl+O", HotkeyAction::FullScreenOcr),
+        ];
+        handler.register_all(&bindings, 300, 800).unwrap();
+
+        let start = Instant::now();
+        let dispatch = |hotkey: &str, state, at| {
+            let event = GlobalHotKeyEvent { id: hotkey_id(hotkey), state };
+            dispatch_event(Some(event), ms(start, at), &handler.actions, &handler.sequences)
+        };
+
+        // 按下并松开一次 Ctrl+C 不会完成 "Ctrl+C Ctrl+C"
+        assert_eq!(dispatch("Ctrl+C", HotKeyState::Pressed, 0).0, None);
+        assert_eq!(dispatch("Ctrl+C", HotKeyState::Released, 50).0, None);
+        assert_eq!(dispatch("Ctrl+C", HotKeyState::Pressed, 200).0, Some(HotkeyAction::OcrClipboardImage));
+        assert_eq!(dispatch("Ctrl+C", HotKeyState::Released, 250).0, None);
+
+        // 等待第二步时临时注册 Ctrl+O，完成或超时后注销
+        let temporary = Mutex::new(Vec::new());
+        let (_, wanted) = dispatch("Ctrl+K", HotKeyState::Pressed, 1000);
+        sync_temporary_keys(registrar.as_ref(), &temporary, &wanted);
+        assert!(registrar.registered.lock().unwrap().contains(&hotkey_id("Ctrl+O")));
+
+        let (_, wanted) = dispatch("Ctrl+K", HotKeyState::Released, 1050);
+        assert_eq!(wanted.len(), 1);
+        let (action, wanted) = dispatch("Ctrl+O", HotKeyState::Pressed, 1200);
+        assert_eq!(action, Some(HotkeyAction::FullScreenOcr));
+        sync_temporary_keys(registrar.as_ref(), &temporary, &wanted);
+        assert!(!registrar.registered.lock().unwrap().contains(&hotkey_id("Ctrl+O")));
+    }
+
+    #[test]
+    fn test_sequence_registers_first_step_only() {
+        let registrar = Arc::new(FakeRegistrar::default());
+        let mut handler = HotKeyHandler::with_registrar(registrar.clone());
+        let bindings = vec![
+            binding("Ctrl+K Ctrl+O", HotkeyAction::FullScreenOcr),
+            binding("Ctrl+K Ctrl+H", HotkeyAction::ShowHistory),
+            binding("Shift Shift", HotkeyAction::RegionSelect),
+        ];
+        handler.register_all(&bindings, 300, 800).unwrap();
+        assert_eq!(*registrar.registered.lock().unwrap(), vec![hotkey_id("Ctrl+K")]);
+
+        handler.register_all(&[], 300, 800).unwrap();
+        assert!(registrar.registered.lock().unwrap().is_empty());
+    }
+}
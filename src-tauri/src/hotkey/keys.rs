@@ -0,0 +1,171 @@
+//! 热键按键表
+
+use global_hotkey::hotkey::Code;
+
+/// 按键表：(键码, 规范名称, 别名)
+///
+/// 格式化时使用规范名称；解析时不区分大小写，规范名称、别名以及 W3C 键码名
+/// （即浏览器 `KeyboardEvent.code`，例如 "KeyA"、"ArrowUp"）都可以识别。
+const KEYS: &[(Code, &str, &[&str])] = &[
+    (Code::KeyA, "A", &["KeyA"]),
+    (Code::KeyB, "B", &["KeyB"]),
+    (Code::KeyC, "C", &["KeyC"]),
+    (Code::KeyD, "D", &["KeyD"]),
+    (Code::KeyE, "E", &["KeyE"]),
+    (Code::KeyF, "F", &["KeyF"]),
+    (Code::KeyG, "G", &["KeyG"]),
+    (Code::KeyH, "H", &["KeyH"]),
+    (Code::KeyI, "I", &["KeyI"]),
+    (Code::KeyJ, "J", &["KeyJ"]),
+    (Code::KeyK, "K", &["KeyK"]),
+    (Code::KeyL, "L", &["KeyL"]),
+    (Code::KeyM, "M", &["KeyM"]),
+    (Code::KeyN, "N", &["KeyN"]),
+    (Code::KeyO, "O", &["KeyO"]),
+    (Code::KeyP, "P", &["KeyP"]),
+    (Code::KeyQ, "Q", &["KeyQ"]),
+    (Code::KeyR, "R", &["KeyR"]),
+    (Code::KeyS, "S", &["KeyS"]),
+    (Code::KeyT, "T", &["KeyT"]),
+    (Code::KeyU, "U", &["KeyU"]),
+    (Code::KeyV, "V", &["KeyV"]),
+    (Code::KeyW, "W", &["KeyW"]),
+    (Code::KeyX, "X", &["KeyX"]),
+    (Code::KeyY, "Y", &["KeyY"]),
+    (Code::KeyZ, "Z", &["KeyZ"]),
+    (Code::Digit0, "0", &["Digit0"]),
+    (Code::Digit1, "1", &["Digit1"]),
+    (Code::Digit2, "2", &["Digit2"]),
+    (Code::Digit3, "3", &["Digit3"]),
+    (Code::Digit4, "4", &["Digit4"]),
+    (Code::Digit5, "5", &["Digit5"]),
+    (Code::Digit6, "6", &["Digit6"]),
+    (Code::Digit7, "7", &["Digit7"]),
+    (Code::Digit8, "8", &["Digit8"]),
+    (Code::Digit9, "9", &["Digit9"]),
+    (Code::F1, "F1", &[]),
+    (Code::F2, "F2", &[]),
+    (Code::F3, "F3", &[]),
+    (Code::F4, "F4", &[]),
+    (Code::F5, "F5", &[]),
+    (Code::F6, "F6", &[]),
+    (Code::F7, "F7", &[]),
+    (Code::F8, "F8", &[]),
+    (Code::F9, "F9", &[]),
+    (Code::F10, "F10", &[]),
+    (Code::F11, "F11", &[]),
+    (Code::F12, "F12", &[]),
+    (Code::F13, "F13", &[]),
+    (Code::F14, "F14", &[]),
+    (Code::F15, "F15", &[]),
+    (Code::F16, "F16", &[]),
+    (Code::F17, "F17", &[]),
+    (Code::F18, "F18", &[]),
+    (Code::F19, "F19", &[]),
+    (Code::F20, "F20", &[]),
+    (Code::F21, "F21", &[]),
+    (Code::F22, "F22", &[]),
+    (Code::F23, "F23", &[]),
+    (Code::F24, "F24", &[]),
+    (Code::ArrowUp, "Up", &["ArrowUp"]),
+    (Code::ArrowDown, "Down", &["ArrowDown"]),
+    (Code::ArrowLeft, "Left", &["ArrowLeft"]),
+    (Code::ArrowRight, "Right", &["ArrowRight"]),
+    (Code::Space, "Space", &[]),
+    (Code::Enter, "Enter", &["Return"]),
+    (Code::Tab, "Tab", &[]),
+    (Code::Backspace, "Backspace", &[]),
+    (Code::Escape, "Esc", &["Escape"]),
+    (Code::Insert, "Insert", &["Ins"]),
+    (Code::Delete, "Delete", &["Del"]),
+    (Code::Home, "Home", &[]),
+    (Code::End, "End", &[]),
+    (Code::PageUp, "PageUp", &["PgUp"]),
+    (Code::PageDown, "PageDown", &["PgDn"]),
+    (Code::PrintScreen, "PrintScreen", &["PrtSc", "PrintScr", "Print"]),
+    (Code::ScrollLock, "ScrollLock", &[]),
+    (Code::Pause, "Pause", &["Break"]),
+    (Code::CapsLock, "CapsLock", &[]),
+    (Code::NumLock, "NumLock", &[]),
+    (Code::ContextMenu, "Menu", &["ContextMenu", "Apps"]),
+    (Code::Minus, "-", &["Minus"]),
+    (Code::Equal, "=", &["Equal"]),
+    (Code::BracketLeft, "[", &["BracketLeft"]),
+    (Code::BracketRight, "]", &["BracketRight"]),
+    (Code::Backslash, "\\", &["Backslash"]),
+    (Code::Semicolon, ";", &["Semicolon"]),
+    (Code::Quote, "'", &["Quote"]),
+    (Code::Backquote, "`", &["Backquote"]),
+    (Code::Comma, ",", &["Comma"]),
+    (Code::Period, ".", &["Period"]),
+    (Code::Slash, "/", &["Slash"]),
+    (Code::Numpad0, "Num0", &["Numpad0"]),
+    (Code::Numpad1, "Num1", &["Numpad1"]),
+    (Code::Numpad2, "Num2", &["Numpad2"]),
+    (Code::Numpad3, "Num3", &["Numpad3"]),
+    (Code::Numpad4, "Num4", &["Numpad4"]),
+    (Code::Numpad5, "Num5", &["Numpad5"]),
+    (Code::Numpad6, "Num6", &["Numpad6"]),
+    (Code::Numpad7, "Num7", &["Numpad7"]),
+    (Code::Numpad8, "Num8", &["Numpad8"]),
+    (Code::Numpad9, "Num9", &["Numpad9"]),
+    (Code::NumpadAdd, "NumAdd", &["NumpadAdd", "NumPlus"]),
+    (Code::NumpadSubtract, "NumSubtract", &["NumpadSubtract", "NumMinus"]),
+    (Code::NumpadMultiply, "NumMultiply", &["NumpadMultiply"]),
+    (Code::NumpadDivide, "NumDivide", &["NumpadDivide"]),
+    (Code::NumpadDecimal, "NumDecimal", &["NumpadDecimal"]),
+    (Code::NumpadEnter, "NumEnter", &["NumpadEnter"]),
+];
+
+/// 按名称查找按键
+pub fn parse_key(name: &str) -> Option<Code> {
+    KEYS.iter()
+        .find(|(_, canonical, aliases)| {
+            canonical.eq_ignore_ascii_case(name) || aliases.iter().any(|a| a.eq_ignore_ascii_case(name))
+        })
+        .map(|(code, _, _)| *code)
+}
+
+/// 按键的规范名称，不在按键表中的键返回 None
+pub fn key_name(code: Code) -> Option<&'static str> {
+    KEYS.iter()
+        .find(|(c, _, _)| *c == code)
+        .map(|(_, canonical, _)| *canonical)
+}
+
+/// 所有支持的按键
+pub fn supported_keys() -> impl Iterator<Item = Code> {
+    KEYS.iter().map(|(code, _, _)| *code)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashSet;
+
+    #[test]
+    fn test_names_are_unique() {
+        let mut names = HashSet::new();
+        for (_, canonical, aliases) in KEYS {
+            for name in std::iter::once(canonical).chain(aliases.iter()) {
+                assert!(names.insert(name.to_ascii_lowercase()), "重复的按键名: {}", name);
+            }
+        }
+
+        let codes: HashSet<_> = supported_keys().collect();
+        assert_eq!(codes.len(), KEYS.len());
+    }
+
+    #[test]
+    fn test_lookup() {
+        assert_eq!(parse_key("a"), Some(Code::KeyA));
+        assert_eq!(parse_key("keya"), Some(Code::KeyA));
+        assert_eq!(parse_key("PgDn"), Some(Code::PageDown));
+        assert_eq!(parse_key("F24"), Some(Code::F24));
+        assert_eq!(parse_key("ArrowLeft"), Some(Code::ArrowLeft));
+        assert_eq!(parse_key("/"), Some(Code::Slash));
+        assert_eq!(parse_key("Nope"), None);
+        assert_eq!(key_name(Code::Escape), Some("Esc"));
+        assert_eq!(key_name(Code::Numpad5), Some("Num5"));
+    }
+}
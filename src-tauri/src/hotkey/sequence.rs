@@ -0,0 +1,231 @@
+//! 按键序列（如 "Ctrl+K Ctrl+O"）的状态机
+
+use global_hotkey::hotkey::HotKey;
+use std::time::{Duration, Instant};
+use crate::config::HotkeyAction;
+
+/// 处理一次系统热键事件的结果
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SequenceOutcome {
+    /// 完成了一个序列
+    Fired(HotkeyAction),
+    /// 匹配了某个序列的前缀，等待下一步
+    Pending,
+    /// 与任何序列都无关
+    Unmatched,
+}
+
+/// 按键序列匹配器，由系统热键的按下事件驱动（松开事件不算一步）
+///
+/// 只有每个序列的第一步常驻注册；匹配到前缀后，下一步需要的按键在超时前临时注册，
+/// 避免长期占用编辑器等程序的快捷键。
+#[derive(Debug)]
+pub struct SequenceMatcher {
+    sequences: Vec<(Vec<HotKey>, HotkeyAction)>,
+    timeout: Duration,
+    /// 常驻注册的热键 ID，不需要临时注册
+    permanent: Vec<u32>,
+    progress: Vec<HotKey>,
+    last_step_at: Option<Instant>,
+}
+
+impl Default for SequenceMatcher {
+    fn default() -> Self {
+        Self::new(Vec::new(), Duration::ZERO, Vec::new())
+    }
+}
+
+impl SequenceMatcher {
+    pub fn new(sequences: Vec<(Vec<HotKey>, HotkeyAction)>, timeout: Duration, permanent: Vec<u32>) -> Self {
+        Self {
+            sequences,
+            timeout,
+            permanent,
+            progress: Vec::new(),
+            last_step_at: None,
+        }
+    }
+
+    /// 需要常驻注册的按键：每个序列的第一步（去重）
+    pub fn first_steps(sequences: &[(Vec<HotKey>, HotkeyAction)]) -> Vec<HotKey> {
+        let mut steps: Vec<HotKey> = Vec::new();
+        for (sequence, _) in sequences {
+            if let Some(first) = sequence.first() {
+                if !steps.contains(first) {
+                    steps.push(*first);
+                }
+            }
+        }
+        steps
+    }
+
+    /// 处理一次系统热键事件
+    pub fn handle(&mut self, id: u32, now: Instant) -> SequenceOutcome {
+        self.poll(now);
+
+        // 先尝试接在当前进度之后，失败时把这次按键当作新序列的开始
+        if !self.progress.is_empty() {
+            if let Some(outcome) = self.advance(self.progress.len(), id, now) {
+                return outcome;
+            }
+        }
+        match self.advance(0, id, now) {
+            Some(outcome) => outcome,
+            None => {
+                self.reset();
+                SequenceOutcome::Unmatched
+            }
+        }
+    }
+
+    /// 检查是否超时，超时时丢弃已输入的前缀，返回是否发生了重置
+    pub fn poll(&mut self, now: Instant) -> bool {
+        match self.last_step_at {
+            Some(last) if now.duration_since(last) > self.timeout => {
+                self.reset();
+                true
+            }
+            _ => false,
+        }
+    }
+
+    /// 当前等待的下一步中需要临时注册的按键
+    pub fn temporary_keys(&self) -> Vec<HotKey> {
+        let mut keys: Vec<HotKey> = Vec::new();
+        if self.progress.is_empty() {
+            return keys;
+        }
+        for (sequence, _) in &self.sequences {
+            if let Some(next) = self.next_step(sequence) {
+                if !self.permanent.contains(&next.id()) && !keys.contains(&next) {
+                    keys.push(next);
+                }
+            }
+        }
+        keys
+    }
+
+    /// 在长度为 `prefix_len` 的已有进度后接上 `id`
+    fn advance(&mut self, prefix_len: usize, id: u32, now: Instant) -> Option<SequenceOutcome> {
+        let prefix = &self.progress[..prefix_len];
+        let mut matched_step = None;
+        let mut fired = None;
+
+        for (sequence, action) in &self.sequences {
+            let Some(step) = sequence.get(prefix_len) else { continue };
+            if step.id() != id || !sequence.starts_with(prefix) {
+                continue;
+            }
+            matched_step = Some(*step);
+            if sequence.len() == prefix_len + 1 {
+                fired = Some(*action);
+            }
+        }
+
+        let step = matched_step?;
+        if let Some(action) = fired {
+            self.reset();
+            return Some(SequenceOutcome::Fired(action));
+        }
+        self.progress.truncate(prefix_len);
+        self.progress.push(step);
+        self.last_step_at = Some(now);
+        Some(SequenceOutcome::Pending)
+    }
+
+    fn next_step(&self, sequence: &[HotKey]) -> Option<HotKey> {
+        if sequence.len() > self.progress.len() && sequence.starts_with(&self.progress) {
+            Some(sequence[self.progress.len()])
+        } else {
+            None
+        }
+    }
+
+    fn reset(&mut self) {
+        self.progress.clear();
+        self.last_step_at = None;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use global_hotkey::hotkey::{Code, Modifiers};
+
+    fn ctrl(code: Code) -> HotKey {
+        HotKey::new(Some(Modifiers::CONTROL), code)
+    }
+
+    fn ms(start: Instant, millis: u64) -> Instant {
+        start + Duration::from_millis(millis)
+    }
+
+    fn matcher() -> SequenceMatcher {
+        let sequences = vec![
+            (vec![ctrl(Code::KeyK), ctrl(Code::KeyO)], HotkeyAction::FullScreenOcr),
+            (vec![ctrl(Code::KeyK), ctrl(Code::KeyH)], HotkeyAction::ShowHistory),
+            (vec![ctrl(Code::KeyC), ctrl(Code::KeyC)], HotkeyAction::OcrClipboardImage),
+        ];
+        let permanent = SequenceMatcher::first_steps(&sequences).iter().map(|k| k.id()).collect();
+        SequenceMatcher::new(sequences, Duration::from_millis(800), permanent)
+    }
+
+    #[test]
+    fn test_chord_fires_within_timeout() {
+        let start = Instant::now();
+        let mut matcher = matcher();
+
+        assert_eq!(matcher.handle(ctrl(Code::KeyK).id(), start), SequenceOutcome::Pending);
+        // 等待下一步时临时注册 Ctrl+O 和 Ctrl+H
+        let temporary: Vec<u32> = matcher.temporary_keys().iter().map(|k| k.id()).collect();
+        assert_eq!(temporary, vec![ctrl(Code::KeyO).id(), ctrl(Code::KeyH).id()]);
+
+        assert_eq!(
+            matcher.handle(ctrl(Code::KeyH).id(), ms(start, 500)),
+            SequenceOutcome::Fired(HotkeyAction::ShowHistory)
+        );
+        assert!(matcher.temporary_keys().is_empty());
+    }
+
+    #[test]
+    fn test_chord_times_out() {
+        let start = Instant::now();
+        let mut matcher = matcher();
+
+        matcher.handle(ctrl(Code::KeyK).id(), start);
+        assert!(!matcher.poll(ms(start, 800)));
+        assert!(matcher.poll(ms(start, 801)));
+        assert!(matcher.temporary_keys().is_empty());
+
+        // 超时后的第二步不再属于序列
+        matcher.handle(ctrl(Code::KeyK).id(), ms(start, 1000));
+        assert_eq!(matcher.handle(ctrl(Code::KeyO).id(), ms(start, 2000)), SequenceOutcome::Unmatched);
+    }
+
+    #[test]
+    fn test_restart_and_repeat() {
+        let start = Instant::now();
+        let mut matcher = matcher();
+
+        // Ctrl+K Ctrl+K Ctrl+O：第二次 Ctrl+K 重新开始
+        matcher.handle(ctrl(Code::KeyK).id(), start);
+        assert_eq!(matcher.handle(ctrl(Code::KeyK).id(), ms(start, 100)), SequenceOutcome::Pending);
+        assert_eq!(
+            matcher.handle(ctrl(Code::KeyO).id(), ms(start, 200)),
+            SequenceOutcome::Fired(HotkeyAction::FullScreenOcr)
+        );
+
+        // 双击同一组合键，第二步已常驻注册，不需要临时注册
+        assert_eq!(matcher.handle(ctrl(Code::KeyC).id(), ms(start, 1000)), SequenceOutcome::Pending);
+        assert!(matcher.temporary_keys().is_empty());
+        assert_eq!(
+            matcher.handle(ctrl(Code::KeyC).id(), ms(start, 1100)),
+            SequenceOutcome::Fired(HotkeyAction::OcrClipboardImage)
+        );
+
+        // 序列中间按下其他热键
+        matcher.handle(ctrl(Code::KeyK).id(), ms(start, 2000));
+        assert_eq!(matcher.handle(ctrl(Code::KeyZ).id(), ms(start, 2100)), SequenceOutcome::Unmatched);
+        assert_eq!(matcher.handle(ctrl(Code::KeyO).id(), ms(start, 2200)), SequenceOutcome::Unmatched);
+    }
+}
@@ -0,0 +1,256 @@
+//! 识别任务管理：为每次识别分配 ID，支持取消、再次触发策略和进度通知
+//!
+//! 热键、托盘和前端命令触发的识别都通过 `JobManager` 执行，同一时间只有一个识别在运行；
+//! 实时识别是周期性的，直接调用流水线，不受再次触发策略影响。
+
+use serde::Serialize;
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use tokio::sync::oneshot;
+use crate::config::RetriggerPolicy;
+use crate::error::AppError;
+use crate::pipeline::{OcrOutput, OcrPipeline, OcrRequest};
+
+pub type JobId = u64;
+
+/// 识别任务所处的阶段
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum JobStage {
+    /// 等待前面的识别完成（排队策略）
+    Queued,
+    Capturing,
+    Preprocessing,
+    Recognizing,
+    Done,
+    Failed,
+    Cancelled,
+}
+
+/// 进度通知，前端通过 `job_id` 取消识别
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub struct JobProgress {
+    pub job_id: JobId,
+    pub stage: JobStage,
+}
+
+type ProgressListener = Arc<dyn Fn(JobProgress) + Send + Sync>;
+
+#[derive(Default)]
+pub struct JobManager {
+    next_id: AtomicU64,
+    /// 正在运行或排队的任务，值用于通知任务取消
+    active: Mutex<HashMap<JobId, oneshot::Sender<()>>>,
+    /// 同一时间只允许一个任务执行流水线，tokio 的锁按等待顺序获取，排队的任务依次执行
+    slot: tokio::sync::Mutex<()>,
+    listener: Mutex<Option<ProgressListener>>,
+}
+
+impl JobManager {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// 设置进度通知（通常是向前端发送 ocr-progress 事件）
+    pub fn set_progress_listener(&self, listener: impl Fn(JobProgress) + Send + Sync + 'static) {
+        *self.listener.lock().unwrap() = Some(Arc::new(listener));
+    }
+
+    /// 正在运行或排队的任务
+    pub fn active_jobs(&self) -> Vec<JobId> {
+        let mut jobs: Vec<JobId> = self.active.lock().unwrap().keys().copied().collect();
+        jobs.sort();
+        jobs
+    }
+
+    /// 取消任务，任务不存在（已经完成）时返回 false
+    pub fn cancel(&self, job_id: JobId) -> bool {
+        match self.active.lock().unwrap().remove(&job_id) {
+            Some(cancel) => {
+                let _ = cancel.send(());
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// 取消全部任务，返回取消的数量
+    pub fn cancel_all(&self) -> usize {
+        let jobs: Vec<_> = self.active.lock().unwrap().drain().collect();
+        let count = jobs.len();
+        for (_, cancel) in jobs {
+            let _ = cancel.send(());
+        }
+        count
+    }
+
+    /// 按再次触发策略执行一次识别
+    pub async fn run(
+        &self,
+        pipeline: &OcrPipeline,
+        request: OcrRequest,
+        policy: RetriggerPolicy,
+    ) -> Result<OcrOutput, AppError> {
+        let (cancel_tx, cancel_rx) = oneshot::channel();
+        let job_id = {
+            let mut active = self.active.lock().unwrap();
+            if !active.is_empty() {
+                match policy {
+                    RetriggerPolicy::Ignore => return Err(AppError::Busy),
+                    RetriggerPolicy::CancelPrevious => {
+                        for (_, cancel) in active.drain() {
+                            let _ = cancel.send(());
+                        }
+                    }
+                    RetriggerPolicy::Queue => {}
+                }
+            }
+            let job_id = self.next_id.fetch_add(1, Ordering::SeqCst) + 1;
+            active.insert(job_id, cancel_tx);
+            job_id
+        };
+
+        let job = async {
+            let _slot = match self.slot.try_lock() {
+                Ok(slot) => slot,
+                Err(_) => {
+                    self.report(job_id, JobStage::Queued);
+                    self.slot.lock().await
+                }
+            };
+            pipeline
+                .run_with_progress(request, |stage| self.report(job_id, stage))
+                .await
+        };
+        // 取消时丢弃流水线的 future，释放执行权；已经交给阻塞线程的识别会在后台结束，结果被丢弃
+        let result = tokio::select! {
+            result = job => result,
+            _ = cancel_rx => Err(AppError::Cancelled),
+        };
+
+        self.active.lock().unwrap().remove(&job_id);
+        let stage = match &result {
+            Ok(_) => JobStage::Done,
+            Err(AppError::Cancelled) => JobStage::Cancelled,
+            Err(_) => JobStage::Failed,
+        };
+        self.report(job_id, stage);
+        result
+    }
+
+    fn report(&self, job_id: JobId, stage: JobStage) {
+        let listener = self.listener.lock().unwrap().clone();
+        if let Some(listener) = listener {
+            listener(JobProgress { job_id, stage });
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+    use tokio::sync::mpsc;
+    use crate::pipeline::{testing, Source, Trigger};
+
+    fn pipeline(delay: Duration) -> Arc<OcrPipeline> {
+        Arc::new(testing::pipeline(40, 30, delay))
+    }
+
+    fn request() -> OcrRequest {
+        OcrRequest::new(Source::PrimaryScreen, Trigger::Command, testing::config())
+    }
+
+    /// 把进度通知转发到通道，测试据此等待任务进入某个阶段
+    fn progress_channel(jobs: &JobManager) -> mpsc::UnboundedReceiver<JobProgress> {
+        let (tx, rx) = mpsc::unbounded_channel();
+        jobs.set_progress_listener(move |progress| {
+            let _ = tx.send(progress);
+        });
+        rx
+    }
+
+    /// 等到某个任务进入 `stage`，返回它的 ID
+    async fn wait_for(progress: &mut mpsc::UnboundedReceiver<JobProgress>, stage: JobStage) -> JobId {
+        loop {
+            let event = progress.recv().await.unwrap();
+            if event.stage == stage {
+                return event.job_id;
+            }
+        }
+    }
+
+    fn spawn_job(
+        jobs: &Arc<JobManager>,
+        pipeline: &Arc<OcrPipeline>,
+        policy: RetriggerPolicy,
+    ) -> tokio::task::JoinHandle<Result<OcrOutput, AppError>> {
+        let (jobs, pipeline) = (jobs.clone(), pipeline.clone());
+        tokio::spawn(async move { jobs.run(&pipeline, request(), policy).await })
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_progress_and_cancel() {
+        let jobs = Arc::new(JobManager::new());
+        let mut progress = progress_channel(&jobs);
+
+        // 没有其他任务时直接执行，不经过排队
+        let fast = pipeline(Duration::ZERO);
+        jobs.run(&fast, request(), RetriggerPolicy::Ignore).await.unwrap();
+        let mut stages = Vec::new();
+        while let Ok(event) = progress.try_recv() {
+            stages.push(event.stage);
+        }
+        assert_eq!(
+            stages,
+            [JobStage::Capturing, JobStage::Preprocessing, JobStage::Recognizing, JobStage::Done]
+        );
+        assert!(jobs.active_jobs().is_empty());
+
+        let slow = pipeline(Duration::from_secs(10));
+        let handle = spawn_job(&jobs, &slow, RetriggerPolicy::Ignore);
+        let job_id = wait_for(&mut progress, JobStage::Recognizing).await;
+        assert_eq!(jobs.active_jobs(), [job_id]);
+        assert!(jobs.cancel(job_id));
+        assert!(matches!(handle.await.unwrap(), Err(AppError::Cancelled)));
+        assert_eq!(wait_for(&mut progress, JobStage::Cancelled).await, job_id);
+        assert!(!jobs.cancel(job_id));
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_retrigger_policies() {
+        let jobs = Arc::new(JobManager::new());
+        let slow = pipeline(Duration::from_millis(200));
+
+        let mut progress = progress_channel(&jobs);
+        let first = spawn_job(&jobs, &slow, RetriggerPolicy::Ignore);
+        let first_id = wait_for(&mut progress, JobStage::Recognizing).await;
+        let ignored = jobs.run(&slow, request(), RetriggerPolicy::Ignore).await;
+        assert!(matches!(ignored, Err(AppError::Busy)));
+
+        let queued = spawn_job(&jobs, &slow, RetriggerPolicy::Queue);
+        let queued_id = wait_for(&mut progress, JobStage::Queued).await;
+        assert_eq!(jobs.active_jobs(), [first_id, queued_id]);
+        assert!(first.await.unwrap().is_ok());
+        assert!(queued.await.unwrap().is_ok());
+
+        let mut progress = progress_channel(&jobs);
+        let previous = spawn_job(&jobs, &slow, RetriggerPolicy::Ignore);
+        wait_for(&mut progress, JobStage::Recognizing).await;
+        let latest = jobs.run(&slow, request(), RetriggerPolicy::CancelPrevious).await;
+        assert!(matches!(previous.await.unwrap(), Err(AppError::Cancelled)));
+        assert!(latest.is_ok());
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_engine_timeout() {
+        let jobs = JobManager::new();
+        let slow = pipeline(Duration::from_secs(10));
+        let mut request = request();
+        request.config.ocr_timeouts_ms.insert(crate::config::OcrEngine::Tesseract, 1000);
+
+        let error = jobs.run(&slow, request, RetriggerPolicy::Ignore).await.unwrap_err();
+        assert!(matches!(error, AppError::Timeout { timeout_ms: 1000 }));
+    }
+}
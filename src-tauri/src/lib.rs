@@ -0,0 +1,12 @@
+//! ScreenOCR 核心库，供桌面应用（`main.rs`）和命令行工具（`bin/screenocr-cli.rs`）共用
+
+pub mod config;
+pub mod error;
+pub mod history;
+pub mod hotkey;
+pub mod jobs;
+pub mod ocr;
+pub mod pipeline;
+pub mod preprocess;
+pub mod screenshot;
+pub mod window;
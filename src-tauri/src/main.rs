@@ -1,21 +1,65 @@
 // Prevents additional console window on Windows in release, DO NOT REMOVE!!
 #![cfg_attr(not(debug_assertions), windows_subsystem = "windows")]
 
-mod screenshot;
-mod ocr;
-mod config;
-mod hotkey;
-
-use serde::Serialize;
-use tauri::{CustomMenuItem, SystemTray, SystemTrayEvent, SystemTrayMenu, Manager, ClipboardManager};
-use std::sync::Mutex;
-use config::AppConfig;
-use hotkey::HotKeyHandler;
+use screenocr_tauri::{config, error, history, hotkey, jobs, ocr, pipeline, screenshot, window};
+use serde::{Deserialize, Serialize};
+use tauri::{CustomMenuItem, SystemTray, SystemTrayEvent, SystemTrayMenu, SystemTraySubmenu, Manager, ClipboardManager};
+use image::codecs::png::CompressionType;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use config::{AppConfig, ConfigError, FieldError, HotkeyAction, ImportMode};
+use config::watcher::ConfigWatcher;
+use error::{AppError, Locale};
+use hotkey::{HotKeyHandler, HotkeyError, HotkeyTrigger};
+use jobs::{JobId, JobManager};
+use ocr::OcrRegistry;
+use pipeline::{HistorySink, OcrOutput, OcrPipeline, OcrRequest, ResultSink, Source, Trigger};
+use screenshot::{CaptureCache, ScreenRegion};
+use history::{HistoryEntry, HistoryStore};
+use window::{ActiveWindowProvider, WindowInfo};
 
 // 全局配置状态
 struct AppState {
     config: Mutex<AppConfig>,
     hotkey_handler: Mutex<HotKeyHandler>,
+    /// 所有识别共用的流水线（截图来源、OCR 引擎和输出）
+    pipeline: OcrPipeline,
+    /// 热键、托盘和前端命令触发的识别任务
+    jobs: JobManager,
+    history: Option<Arc<HistoryStore>>,
+    /// 上一次识别的区域（虚拟桌面坐标）
+    last_region: Mutex<Option<ScreenRegion>>,
+    /// 实时识别是否开启
+    live_mode: AtomicBool,
+    /// 实时识别的会话编号，每次开启或关闭时加一，旧会话的识别循环发现编号变化后退出
+    live_session: AtomicU64,
+    /// 配置文件监听器，在 setup 中启动
+    config_watcher: Mutex<Option<ConfigWatcher>>,
+    /// 前台窗口查询，用于按 profile_rules 自动选择方案
+    window_provider: Arc<dyn ActiveWindowProvider>,
+    /// 热键按下时的前台窗口（区域选择等会打开本程序的窗口，之后再查询就不准了）
+    trigger_window: Mutex<Option<WindowInfo>>,
+    /// 通过 capture:// 协议发给前端的截图
+    captures: CaptureCache,
+}
+
+/// 前端读取截图的自定义协议，地址为 `capture://localhost/<id>`（Windows 上是 `https://capture.localhost/<id>`）
+const CAPTURE_PROTOCOL: &str = "capture";
+/// 协议保留的截图数量
+const CAPTURE_CACHE_SIZE: usize = 4;
+
+/// 实时识别的间隔
+const LIVE_MODE_INTERVAL_MS: u64 = 1000;
+
+/// 托盘菜单中方案项的 ID 前缀，后接方案序号
+const PROFILE_MENU_PREFIX: &str = "profile:";
+
+/// 按前端的界面语言（`navigator.language`）设置错误信息的语言
+#[tauri::command]
+fn set_locale(tag: String) {
+    Locale::set(Locale::parse(&tag));
 }
 
 #[tauri::command]
@@ -25,209 +69,773 @@ fn get_config(state: tauri::State<AppState>) -> AppConfig {
 }
 
 #[tauri::command]
-fn update_config(config: AppConfig, state: tauri::State<AppState>) -> Result<(), String> {
-    // 保存到文件
-    config.save().map_err(|e| format!("保存配置失败: {}", e))?;
-    
-    // 更新热键
-    let mut hotkey_handler = state.hotkey_handler.lock().unwrap();
-    hotkey_handler.register(&config.hotkey)?;
+fn update_config(
+    mut config: AppConfig,
+    app: tauri::AppHandle,
+    state: tauri::State<AppState>,
+) -> Result<(), AppError> {
+    config.sync_active_profile();
     
-    // 更新内存中的配置
+    // 保存完成前一直持有锁，配置文件监听线程据此识别本程序自己的写入
     let mut app_config = state.config.lock().unwrap();
-    *app_config = config.clone();
+    commit_config(&state, &mut app_config, config.clone())?;
+    drop(app_config);
     
+    refresh_tray_menu(&app, &config);
     println!("✅ 配置已更新并保存: {:?}", config);
     Ok(())
 }
 
-#[tauri::command]
-fn reset_config(state: tauri::State<AppState>) -> Result<AppConfig, String> {
-    let config = AppConfig::reset().map_err(|e| format!("重置配置失败: {}", e))?;
+/// 校验并保存新配置，然后让它生效，调用方需要持有配置锁
+fn commit_config(state: &AppState, current: &mut AppConfig, config: AppConfig) -> Result<(), ConfigError> {
+    config.validate()?;
+    
+    // 先更新热键，注册失败时会恢复原来的热键，配置不会被保存
+    register_config_hotkeys(state, &config)?;
+    
+    // 保存到文件
+    config.save().map_err(ConfigError::Save)?;
+    
+    // 更新 OCR 引擎设置
+    state.pipeline.registry().configure(&config);
     
+    // 更新内存中的配置
+    *current = config;
+    Ok(())
+}
+
+/// 在当前配置上修改并保存，通知前端和托盘菜单，返回新的配置
+fn update_config_with(
+    app: &tauri::AppHandle,
+    change: impl FnOnce(&mut AppConfig) -> Result<(), ConfigError>,
+) -> Result<AppConfig, AppError> {
+    let state = app.state::<AppState>();
     let mut app_config = state.config.lock().unwrap();
-    *app_config = config.clone();
+    let mut config = app_config.clone();
+    change(&mut config)?;
+    commit_config(&state, &mut app_config, config.clone())?;
+    drop(app_config);
     
-    println!("🔄 配置已重置");
+    refresh_tray_menu(app, &config);
+    let _ = app.emit_all("config-changed", config.clone());
     Ok(config)
 }
 
+/// 用当前设置创建方案并设为当前方案
 #[tauri::command]
-async fn capture_screen() -> Result<CaptureResponse, String> {
-    println!("📸 开始屏幕截图...");
-    
-    let capture = screenshot::capture_primary_screen()
-        .map_err(|e| format!("截图失败: {}", e))?;
-    
-    let base64_image = screenshot::to_base64_png(&capture)
-        .map_err(|e| format!("图像转换失败: {}", e))?;
-    
-    println!("✅ 截图完成: {}x{}", capture.width, capture.height);
-    
-    Ok(CaptureResponse {
-        width: capture.width,
-        height: capture.height,
-        image_base64: base64_image,
-    })
+fn create_profile(name: String, app: tauri::AppHandle) -> Result<AppConfig, AppError> {
+    update_config_with(&app, |config| config.create_profile(&name))
 }
 
 #[tauri::command]
-async fn perform_ocr_on_screen(
-    app: tauri::AppHandle,
-    state: tauri::State<'_, AppState>,
-) -> Result<OcrResponse, String> {
-    println!("🔍 开始 OCR 识别...");
+fn rename_profile(name: String, new_name: String, app: tauri::AppHandle) -> Result<AppConfig, AppError> {
+    update_config_with(&app, |config| config.rename_profile(&name, &new_name))
+}
+
+#[tauri::command]
+fn delete_profile(name: String, app: tauri::AppHandle) -> Result<AppConfig, AppError> {
+    update_config_with(&app, |config| config.delete_profile(&name))
+}
+
+#[tauri::command]
+fn duplicate_profile(name: String, new_name: String, app: tauri::AppHandle) -> Result<AppConfig, AppError> {
+    update_config_with(&app, |config| config.duplicate_profile(&name, &new_name))
+}
+
+#[tauri::command]
+fn activate_profile(name: String, app: tauri::AppHandle) -> Result<AppConfig, AppError> {
+    let config = update_config_with(&app, |config| config.activate_profile(&name))?;
+    println!("📂 已切换到方案: {}", name);
+    Ok(config)
+}
+
+/// 按序号切换方案（托盘菜单和方案热键），会重新注册热键，需要在主线程上调用
+fn activate_profile_at(app: &tauri::AppHandle, index: usize) -> Result<(), AppError> {
+    let name = app.state::<AppState>().config.lock().unwrap()
+        .profiles
+        .get(index)
+        .map(|profile| profile.name.clone())
+        .ok_or_else(|| ConfigError::UnknownProfile(format!("#{}", index + 1)))?;
+    activate_profile(name, app.clone()).map(|_| ())
+}
+
+/// 系统托盘菜单，有方案时显示“配置方案”子菜单并勾选当前方案
+fn tray_menu(config: &AppConfig) -> SystemTrayMenu {
+    let mut menu = SystemTrayMenu::new()
+        .add_item(CustomMenuItem::new("ocr_now".to_string(), "立即识别"));
+    
+    if !config.profiles.is_empty() {
+        let mut profiles = SystemTrayMenu::new();
+        for (i, profile) in config.profiles.iter().enumerate() {
+            let mut item = CustomMenuItem::new(format!("{}{}", PROFILE_MENU_PREFIX, i), &profile.name);
+            if config.active_profile.as_deref() == Some(profile.name.as_str()) {
+                item = item.selected();
+            }
+            profiles = profiles.add_item(item);
+        }
+        menu = menu.add_submenu(SystemTraySubmenu::new("配置方案", profiles));
+    }
     
-    // 获取配置
-    let config = {
-        let cfg = state.config.lock().unwrap();
-        cfg.clone()
-    };
+    menu.add_native_item(tauri::SystemTrayMenuItem::Separator)
+        .add_item(CustomMenuItem::new("settings".to_string(), "设置"))
+        .add_native_item(tauri::SystemTrayMenuItem::Separator)
+        .add_item(CustomMenuItem::new("quit".to_string(), "退出"))
+}
+
+fn refresh_tray_menu(app: &tauri::AppHandle, config: &AppConfig) {
+    if let Err(e) = app.tray_handle().set_menu(tray_menu(config)) {
+        eprintln!("⚠️  更新托盘菜单失败: {}", e);
+    }
+}
+
+/// 按配置重新注册全部热键，失败时恢复原来的热键
+fn register_config_hotkeys(state: &AppState, config: &AppConfig) -> Result<(), ConfigError> {
+    state.hotkey_handler.lock().unwrap()
+        .register_all(&config.all_hotkey_bindings(), config.trigger_delay_ms, config.sequence_timeout_ms)
+        .map_err(|e| ConfigError::Invalid(vec![FieldError::new(hotkey_field(config, e.hotkey()), e.to_string())]))
+}
+
+/// 配置文件被手动修改后让新配置生效，并通知前端
+///
+/// 在配置文件监听线程上调用，全局热键只能在主线程上注册，所以切换到主线程执行。
+fn reload_config(app: &tauri::AppHandle, result: Result<AppConfig, ConfigError>) {
+    let handle = app.clone();
+    if let Err(e) = app.run_on_main_thread(move || apply_reloaded_config(&handle, result)) {
+        eprintln!("⚠️  重新加载配置失败: {}", e);
+    }
+}
+
+fn apply_reloaded_config(app: &tauri::AppHandle, result: Result<AppConfig, ConfigError>) {
+    let state = app.state::<AppState>();
+    let result = result.and_then(|config| {
+        let mut app_config = state.config.lock().unwrap();
+        // 本程序自己保存的内容
+        if *app_config == config {
+            return Ok(None);
+        }
+        register_config_hotkeys(&state, &config)?;
+        state.pipeline.registry().configure(&config);
+        *app_config = config.clone();
+        Ok(Some(config))
+    });
     
-    // 添加延时
-    if config.trigger_delay_ms > 0 {
-        tokio::time::sleep(tokio::time::Duration::from_millis(config.trigger_delay_ms as u64)).await;
+    match result {
+        Ok(Some(config)) => {
+            println!("🔄 已重新加载配置文件");
+            refresh_tray_menu(app, &config);
+            let _ = app.emit_all("config-changed", config);
+        }
+        Ok(None) => {}
+        Err(e) => {
+            eprintln!("⚠️  配置文件的修改未生效: {}", e);
+            emit_error(app, "config-error", &AppError::from(e));
+        }
+    }
+}
+
+/// 出错的热键对应的配置字段
+fn hotkey_field(config: &AppConfig, hotkey: &str) -> String {
+    let same = |candidate: &str| {
+        candidate == hotkey || HotKeyHandler::normalize_hotkey(candidate).is_ok_and(|normalized| normalized == hotkey)
+    };
+    if same(&config.hotkey) {
+        return "hotkey".to_string();
+    }
+    if let Some(i) = config.hotkey_bindings.iter().position(|binding| same(&binding.hotkey)) {
+        return format!("hotkey_bindings[{}].hotkey", i);
     }
+    config
+        .profiles
+        .iter()
+        .position(|profile| profile.hotkey.as_deref().is_some_and(same))
+        .map(|i| format!("profiles[{}].hotkey", i))
+        .unwrap_or_else(|| "hotkey_bindings".to_string())
+}
+
+#[tauri::command]
+fn reset_config(app: tauri::AppHandle, state: tauri::State<AppState>) -> Result<AppConfig, AppError> {
+    let config = AppConfig::default();
+    // 与 update_config 相同：自定义热键绑定、方案热键和按键序列也恢复为默认
+    let mut app_config = state.config.lock().unwrap();
+    commit_config(&state, &mut app_config, config.clone())?;
+    drop(app_config);
+    refresh_tray_menu(&app, &config);
+    let _ = app.emit_all("config-changed", config.clone());
     
-    // 截图
-    let capture = screenshot::capture_primary_screen()
-        .map_err(|e| format!("截图失败: {}", e))?;
+    println!("🔄 配置已重置");
+    Ok(config)
+}
+
+/// 导出当前配置到文件
+#[tauri::command]
+fn export_config(path: String, state: tauri::State<AppState>) -> Result<(), AppError> {
+    let config = state.config.lock().unwrap().clone();
+    config.export_to(Path::new(&path)).map_err(|e| ConfigError::Save(e).into())
+}
+
+/// 从文件导入配置，校验通过后保存并生效
+#[tauri::command]
+fn import_config(path: String, mode: ImportMode, app: tauri::AppHandle) -> Result<AppConfig, AppError> {
+    let content = std::fs::read_to_string(&path)
+        .map_err(|e| ConfigError::Parse(anyhow::Error::new(e).context(format!("读取导入文件失败: {}", path))))?;
+    let config = update_config_with(&app, |config| {
+        *config = config.imported(&content, mode)?;
+        Ok(())
+    })?;
+    println!("📥 已导入配置 ({:?}): {}", mode, path);
+    Ok(config)
+}
+
+/// 当前使用的配置文件路径
+#[tauri::command]
+fn get_config_path() -> Result<String, AppError> {
+    AppConfig::get_config_path()
+        .map(|path| path.display().to_string())
+        .map_err(AppError::Internal)
+}
+
+/// 保存前检查热键：语法、与其他绑定是否重复、是否被其他程序占用，成功时返回规范格式
+///
+/// `index` 是正在修改的 `hotkey_bindings` 项，不传时表示主热键 `hotkey`。
+#[tauri::command]
+fn check_hotkey(
+    hotkey: String,
+    action: Option<HotkeyAction>,
+    index: Option<usize>,
+    state: tauri::State<AppState>,
+) -> Result<String, AppError> {
+    let (bindings, editing) = {
+        let config = state.config.lock().unwrap();
+        (config.all_hotkey_bindings(), config.hotkey_binding_position(index))
+    };
+    state.hotkey_handler.lock().unwrap().check(
+        &hotkey,
+        action.unwrap_or(HotkeyAction::FullScreenOcr),
+        &bindings,
+        editing,
+    )
+    .map_err(AppError::from)
+}
+
+#[tauri::command]
+fn normalize_hotkey(hotkey: String) -> Result<String, AppError> {
+    HotKeyHandler::normalize_hotkey(&hotkey)
+        .map_err(|reason| HotkeyError::InvalidSyntax { hotkey, reason }.into())
+}
+
+#[tauri::command]
+fn list_ocr_engines(state: tauri::State<AppState>) -> Vec<ocr::OcrEngineInfo> {
+    state.pipeline.registry().describe()
+}
+
+#[tauri::command]
+async fn capture_screen(
+    state: tauri::State<'_, AppState>,
+    transport: Option<CaptureTransport>,
+) -> Result<CaptureResponse, AppError> {
+    println!("📸 开始屏幕截图...");
     
-    println!("📸 截图完成: {}x{}", capture.width, capture.height);
+    let capture = state.pipeline.capture(Source::PrimaryScreen).await?;
+    let (width, height) = (capture.width, capture.height);
     
-    // OCR 识别
-    let engine = match config.ocr_engine.as_str() {
-        "WeChatOCR" => ocr::OcrEngine::WeChatOCR,
-        _ => ocr::OcrEngine::Tesseract,
+    let response = match transport.unwrap_or_default() {
+        CaptureTransport::Base64 => {
+            let base64_image = screenshot::to_base64_png(&capture)
+                .map_err(|e| AppError::Internal(e.context("图像转换失败")))?;
+            CaptureResponse { width, height, image_base64: Some(base64_image), capture_id: None }
+        }
+        CaptureTransport::Protocol => {
+            // 协议处理函数在界面线程上执行，提前在阻塞线程上编码
+            let png = tokio::task::spawn_blocking(move || screenshot::encode_png(&capture, CompressionType::Fast))
+                .await
+                .map_err(anyhow::Error::from)
+                .and_then(|png| png)
+                .map_err(|e| AppError::Internal(e.context("图像转换失败")))?;
+            let capture_id = state.captures.insert(png);
+            CaptureResponse { width, height, image_base64: None, capture_id: Some(capture_id) }
+        }
     };
     
-    let ocr_result = ocr::perform_ocr(&capture, engine).await
-        .map_err(|e| format!("OCR 识别失败: {}", e))?;
-    
-    println!("✅ OCR 完成，识别了 {} 个字符", ocr_result.text.len());
+    println!("✅ 截图完成: {}x{}", width, height);
     
-    // 自动复制到剪贴板
-    if config.auto_copy && !ocr_result.text.is_empty() {
-        if let Err(e) = app.clipboard_manager().write_text(ocr_result.text.clone()) {
-            eprintln!("⚠️  复制到剪贴板失败: {}", e);
-        } else {
-            println!("📋 已复制到剪贴板");
-        }
+    Ok(response)
+}
+
+/// 处理 capture:// 请求：按 ID 返回 PNG（快速压缩），截图不存在时返回 404
+fn serve_capture(
+    app: &tauri::AppHandle,
+    request: &tauri::http::Request,
+) -> Result<tauri::http::Response, Box<dyn std::error::Error>> {
+    let png = request
+        .uri()
+        .split(['?', '#'])
+        .next()
+        .and_then(|path| path.trim_end_matches('/').rsplit('/').next())
+        .and_then(|id| app.state::<AppState>().captures.get(id));
+    
+    match png {
+        Some(png) => tauri::http::ResponseBuilder::new()
+            .mimetype("image/png")
+            .body(png.to_vec()),
+        None => tauri::http::ResponseBuilder::new().status(404).body(Vec::new()),
     }
+}
+
+#[tauri::command]
+async fn perform_ocr_on_screen(state: tauri::State<'_, AppState>) -> Result<OcrResponse, AppError> {
+    ocr_screen(&state, Trigger::Command, true).await
+}
+
+/// 全屏 OCR，`apply_delay` 为 false 时跳过触发延时（按住修饰键触发时已经等待过）
+async fn ocr_screen(state: &AppState, trigger: Trigger, apply_delay: bool) -> Result<OcrResponse, AppError> {
+    println!("🔍 开始 OCR 识别...");
     
-    Ok(OcrResponse {
-        text: ocr_result.text,
-        confidence: ocr_result.confidence,
-        language: ocr_result.language,
-    })
+    let config = config_for_trigger(state);
+    let delay = if apply_delay { config.trigger_delay_ms as u64 } else { 0 };
+    let request = OcrRequest::new(Source::PrimaryScreen, trigger, config)
+        .with_delay(Duration::from_millis(delay));
+    run_pipeline(state, request).await
 }
 
+/// 识别前端选择的区域，坐标为窗口内的逻辑像素（CSS 像素）
+///
+/// 窗口原点使用物理像素坐标，只有窗口内的偏移按窗口所在显示器的缩放比例换算。
 #[tauri::command]
 async fn perform_ocr_on_region(
-    app: tauri::AppHandle,
+    window: tauri::Window,
     state: tauri::State<'_, AppState>,
-    x: u32,
-    y: u32,
-    width: u32,
-    height: u32,
-) -> Result<OcrResponse, String> {
-    println!("🔍 开始区域 OCR 识别: {}x{} at ({}, {})", width, height, x, y);
+    x: f64,
+    y: f64,
+    width: f64,
+    height: f64,
+) -> Result<OcrResponse, AppError> {
+    let window_error = |e: tauri::Error| AppError::Internal(anyhow::Error::from(e).context("获取窗口位置失败"));
+    let origin = window.inner_position().map_err(window_error)?;
+    let scale_factor = window.scale_factor().map_err(window_error)?;
+    // 截取区域（虚拟桌面坐标，可跨越多个显示器）
+    let region = ScreenRegion::from_window((origin.x, origin.y), x, y, width, height, scale_factor);
+    *state.last_region.lock().unwrap() = Some(region);
+    
+    ocr_region(&state, Trigger::Command, region).await
+}
+
+/// 识别虚拟桌面中的区域
+async fn ocr_region(state: &AppState, trigger: Trigger, region: ScreenRegion) -> Result<OcrResponse, AppError> {
+    println!(
+        "🔍 开始区域 OCR 识别: {}x{} at ({}, {})",
+        region.width, region.height, region.x, region.y
+    );
+    
+    let config = config_for_trigger(state);
+    run_pipeline(state, OcrRequest::new(Source::Region(region), trigger, config)).await
+}
+
+/// 识别剪贴板中的图片（自动复制会替换剪贴板中的图片）
+async fn ocr_clipboard_image(state: &AppState, trigger: Trigger) -> Result<OcrResponse, AppError> {
+    println!("🔍 开始识别剪贴板图片...");
     
-    // 获取配置
-    let config = {
-        let cfg = state.config.lock().unwrap();
-        cfg.clone()
+    let config = config_for_trigger(state);
+    run_pipeline(state, OcrRequest::new(Source::Clipboard, trigger, config)).await
+}
+
+/// 作为识别任务执行流水线，返回发送给前端的结果
+async fn run_pipeline(state: &AppState, request: OcrRequest) -> Result<OcrResponse, AppError> {
+    let policy = request.config.retrigger_policy;
+    state.jobs.run(&state.pipeline, request, policy).await
+        .map(|output| OcrResponse::from_output(&output))
+}
+
+/// 取消识别任务，不指定 `job_id` 时取消全部，返回取消的数量
+#[tauri::command]
+fn cancel_ocr(job_id: Option<JobId>, state: tauri::State<AppState>) -> usize {
+    let cancelled = match job_id {
+        Some(job_id) => state.jobs.cancel(job_id) as usize,
+        None => state.jobs.cancel_all(),
     };
+    if cancelled > 0 {
+        println!("⏹️  已取消 {} 个识别任务", cancelled);
+    }
+    cancelled
+}
+
+/// 把错误以 `{ code, message, details }` 的形式通过事件发送给前端
+fn emit_error(app: &tauri::AppHandle, event: &str, error: &AppError) {
+    match serde_json::to_value(error) {
+        Ok(payload) => {
+            let _ = app.emit_all(event, payload);
+        }
+        Err(e) => eprintln!("⚠️  序列化错误失败: {}", e),
+    }
+}
+
+/// 本次识别使用的配置：有 profile_rules 匹配前台窗口时使用对应方案的设置
+///
+/// 优先使用热键按下时记录的窗口，没有记录时查询当前前台窗口。
+fn config_for_trigger(state: &AppState) -> AppConfig {
+    let recorded = state.trigger_window.lock().unwrap().take();
+    let config = state.config.lock().unwrap().clone();
+    let window = config.trigger_window(recorded, state.window_provider.as_ref());
+    config_for_window(config, window.as_ref())
+}
+
+/// 按窗口匹配的方案调整配置
+fn config_for_window(mut config: AppConfig, window: Option<&WindowInfo>) -> AppConfig {
+    let Some(window) = window else {
+        return config;
+    };
+    if let Some(profile) = config.profile_for_window(window).cloned() {
+        println!("🪟 前台窗口 {} ({}) 使用方案: {}", window.class, window.title, profile.name);
+        config.apply_profile_settings(&profile);
+    }
+    config
+}
+
+/// 查询选择方案依据的前台窗口，查询在配置锁之外进行，不阻塞其他需要配置的命令
+fn query_trigger_window(state: &AppState) -> Option<WindowInfo> {
+    let config = state.config.lock().unwrap().clone();
+    config.trigger_window(None, state.window_provider.as_ref())
+}
+
+/// 区域选择被取消时丢弃热键按下时记录的窗口，避免之后的识别误用
+#[tauri::command]
+fn cancel_region_select(state: tauri::State<AppState>) {
+    state.trigger_window.lock().unwrap().take();
+}
+
+/// 执行热键绑定的动作
+async fn run_hotkey_action(app: tauri::AppHandle, trigger: HotkeyTrigger) {
+    println!("🎯 热键触发: {:?}", trigger.action);
+    let state = app.state::<AppState>();
+    
+    // 在打开任何窗口之前记录前台窗口
+    let recognizes = !matches!(
+        trigger.action,
+        HotkeyAction::ShowHistory | HotkeyAction::ToggleLiveMode | HotkeyAction::ActivateProfile(_)
+    );
+    if recognizes {
+        let window = query_trigger_window(&state);
+        *state.trigger_window.lock().unwrap() = window;
+    }
     
-    // 截图全屏
-    let capture = screenshot::capture_primary_screen()
-        .map_err(|e| format!("截图失败: {}", e))?;
-    
-    println!("📸 截图完成: {}x{}", capture.width, capture.height);
-    
-    // 裁剪区域
-    let region = screenshot::CaptureRegion {
-        x,
-        y,
-        width,
-        height,
+    let result = match trigger.action {
+        HotkeyAction::FullScreenOcr => {
+            ocr_screen(&state, Trigger::Hotkey, !trigger.delay_elapsed).await.map(Some)
+        }
+        HotkeyAction::RepeatLastRegion => {
+            let region = *state.last_region.lock().unwrap();
+            match region {
+                Some(region) => ocr_region(&state, Trigger::Hotkey, region).await.map(Some),
+                None => Err(AppError::NoRegion),
+            }
+        }
+        HotkeyAction::OcrClipboardImage => ocr_clipboard_image(&state, Trigger::Hotkey).await.map(Some),
+        HotkeyAction::ShowHistory => {
+            show_main_window(&app);
+            let _ = app.emit_all("show-history", ());
+            Ok(None)
+        }
+        HotkeyAction::ToggleLiveMode => toggle_live_mode(&app).map(|_| None),
+        HotkeyAction::ActivateProfile(index) => {
+            // 切换方案会重新注册全部热键，只能在主线程上执行（与托盘菜单相同）
+            let handle = app.clone();
+            app.run_on_main_thread(move || {
+                if let Err(e) = activate_profile_at(&handle, index) {
+                    eprintln!("⚠️  切换方案失败: {}", e);
+                    emit_error(&handle, "ocr-error", &e);
+                }
+            })
+            .map(|_| None)
+            .map_err(|e| AppError::Internal(e.into()))
+        }
+        // 区域选择覆盖层由前端根据 hotkey-triggered 事件打开
+        HotkeyAction::RegionSelect => Ok(None),
     };
     
-    let cropped = screenshot::crop_region(&capture, &region)
-        .map_err(|e| format!("裁剪区域失败: {}", e))?;
+    match result {
+        // 结果由 EventSink 发送到前端
+        Ok(Some(result)) => println!("✅ OCR 成功: {} 个字符", result.text.len()),
+        Ok(None) => {}
+        // 再次触发被忽略或识别被取消不算失败
+        Err(e @ (AppError::Busy | AppError::Cancelled)) => println!("ℹ️  {}", e),
+        Err(e) => {
+            eprintln!("❌ OCR 失败: {}", e);
+            emit_error(&app, "ocr-error", &e);
+        }
+    }
+}
+
+/// 开关实时识别：开启后持续识别上一次选择的区域，文字变化时发送 live-ocr-result 事件
+fn toggle_live_mode(app: &tauri::AppHandle) -> Result<(), AppError> {
+    let state = app.state::<AppState>();
+    
+    if state.live_mode.swap(false, Ordering::SeqCst) {
+        state.live_session.fetch_add(1, Ordering::SeqCst);
+        println!("⏹️  实时识别已关闭");
+        let _ = app.emit_all("live-mode-changed", false);
+        return Ok(());
+    }
+    
+    if state.last_region.lock().unwrap().is_none() {
+        return Err(AppError::NoRegion);
+    }
     
-    println!("✂️  区域裁剪完成: {}x{}", cropped.width, cropped.height);
+    // 关闭后马上重新开启时，上一次的循环可能还在等待，靠会话编号让它退出
+    let session = state.live_session.fetch_add(1, Ordering::SeqCst) + 1;
+    state.live_mode.store(true, Ordering::SeqCst);
+    println!("▶️  实时识别已开启");
+    let _ = app.emit_all("live-mode-changed", true);
     
-    // OCR 识别
-    let engine = match config.ocr_engine.as_str() {
-        "WeChatOCR" => ocr::OcrEngine::WeChatOCR,
-        _ => ocr::OcrEngine::Tesseract,
-    };
+    // 开启时确定一次前台窗口，之后每次识别按它选择方案，不再反复查询
+    let window = query_trigger_window(&state);
     
-    let ocr_result = ocr::perform_ocr(&cropped, engine).await
-        .map_err(|e| format!("OCR 识别失败: {}", e))?;
+    let app = app.clone();
+    tauri::async_runtime::spawn(async move {
+        let mut last_text = String::new();
+        loop {
+            tokio::time::sleep(Duration::from_millis(LIVE_MODE_INTERVAL_MS)).await;
+            
+            let state = app.state::<AppState>();
+            let current = || state.live_session.load(Ordering::SeqCst) == session;
+            if !current() {
+                break;
+            }
+            let region = match *state.last_region.lock().unwrap() {
+                Some(region) => region,
+                None => continue,
+            };
+            
+            let result = live_ocr(&state, region, window.as_ref()).await;
+            if !current() {
+                break;
+            }
+            match result {
+                Ok(result) if result.text != last_text => {
+                    last_text = result.text.clone();
+                    let _ = app.emit_all("live-ocr-result", result);
+                }
+                Ok(_) => {}
+                Err(e) => eprintln!("⚠️  实时识别失败: {}", e),
+            }
+        }
+    });
     
-    println!("✅ OCR 完成，识别了 {} 个字符", ocr_result.text.len());
+    Ok(())
+}
+
+/// 实时识别的单次识别，不写入历史也不复制到剪贴板
+async fn live_ocr(state: &AppState, region: ScreenRegion, window: Option<&WindowInfo>) -> Result<OcrResponse, AppError> {
+    let config = state.config.lock().unwrap().for_window(window);
+    // 周期性识别不作为任务执行，不受再次触发策略影响
+    state.pipeline.run(OcrRequest::new(Source::Region(region), Trigger::Live, config)).await
+        .map(|output| OcrResponse::from_output(&output))
+}
+
+fn show_main_window(app: &tauri::AppHandle) {
+    if let Some(window) = app.get_window("main") {
+        let _ = window.show();
+        let _ = window.set_focus();
+    }
+}
+
+/// 自动复制识别结果（实时识别除外）
+struct ClipboardSink(tauri::AppHandle);
+
+impl ResultSink for ClipboardSink {
+    fn name(&self) -> &str {
+        "复制到剪贴板"
+    }
     
-    // 自动复制到剪贴板
-    if config.auto_copy && !ocr_result.text.is_empty() {
-        if let Err(e) = app.clipboard_manager().write_text(ocr_result.text.clone()) {
-            eprintln!("⚠️  复制到剪贴板失败: {}", e);
-        } else {
-            println!("📋 已复制到剪贴板");
+    fn deliver(&self, output: &OcrOutput) -> anyhow::Result<()> {
+        if output.trigger == Trigger::Live || !output.config.auto_copy || output.result.text.is_empty() {
+            return Ok(());
         }
+        self.0.clipboard_manager().write_text(output.result.text.clone())?;
+        println!("📋 已复制到剪贴板");
+        Ok(())
+    }
+}
+
+/// 把热键触发的识别结果通过 ocr-result 事件发送到前端（命令调用直接返回结果）
+struct EventSink(tauri::AppHandle);
+
+impl ResultSink for EventSink {
+    fn name(&self) -> &str {
+        "发送识别结果"
     }
     
-    Ok(OcrResponse {
-        text: ocr_result.text,
-        confidence: ocr_result.confidence,
-        language: ocr_result.language,
-    })
+    fn deliver(&self, output: &OcrOutput) -> anyhow::Result<()> {
+        if output.trigger == Trigger::Hotkey {
+            self.0.emit_all("ocr-result", OcrResponse::from_output(output))?;
+        }
+        Ok(())
+    }
+}
+
+/// 获取历史存储，未启用时返回错误
+fn history_store(state: &AppState) -> Result<&HistoryStore, AppError> {
+    state.history.as_deref().ok_or(AppError::HistoryUnavailable)
+}
+
+#[tauri::command]
+fn list_history(
+    state: tauri::State<AppState>,
+    limit: Option<usize>,
+    offset: Option<usize>,
+) -> Result<Vec<HistoryEntry>, AppError> {
+    history_store(&state)?
+        .list(limit.unwrap_or(50), offset.unwrap_or(0))
+        .map_err(AppError::History)
+}
+
+#[tauri::command]
+fn search_history(
+    state: tauri::State<AppState>,
+    query: String,
+    limit: Option<usize>,
+) -> Result<Vec<HistoryEntry>, AppError> {
+    history_store(&state)?
+        .search(&query, limit.unwrap_or(50))
+        .map_err(AppError::History)
+}
+
+#[tauri::command]
+fn pin_history_entry(state: tauri::State<AppState>, id: i64, pinned: bool) -> Result<bool, AppError> {
+    history_store(&state)?
+        .set_pinned(id, pinned)
+        .map_err(AppError::History)
+}
+
+#[tauri::command]
+fn delete_history_entry(state: tauri::State<AppState>, id: i64) -> Result<bool, AppError> {
+    history_store(&state)?
+        .delete(id)
+        .map_err(AppError::History)
+}
+
+#[tauri::command]
+fn purge_history(state: tauri::State<AppState>, keep_pinned: Option<bool>) -> Result<usize, AppError> {
+    let removed = history_store(&state)?
+        .purge(keep_pinned.unwrap_or(true))
+        .map_err(AppError::History)?;
+    println!("🗑️  已清除 {} 条历史记录", removed);
+    Ok(removed)
+}
+
+/// 截图发给前端的方式
+#[derive(Debug, Clone, Copy, Default, Deserialize)]
+#[serde(rename_all = "snake_case")]
+enum CaptureTransport {
+    /// PNG 以 Base64 放在响应里
+    #[default]
+    Base64,
+    /// 响应只带截图 ID，前端通过 capture:// 协议读取
+    Protocol,
 }
 
 #[derive(Debug, Serialize)]
 struct CaptureResponse {
     width: u32,
     height: u32,
-    image_base64: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    image_base64: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    capture_id: Option<String>,
 }
 
-#[derive(Debug, Serialize)]
+#[derive(Debug, Clone, Serialize)]
 struct OcrResponse {
     text: String,
     confidence: f32,
     language: String,
+    /// 识别区域左上角在虚拟桌面中的坐标，`blocks` 中的边界框以此为原点
+    x: i32,
+    y: i32,
+    blocks: Vec<ocr::OcrBlock>,
+}
+
+impl OcrResponse {
+    fn from_output(output: &OcrOutput) -> Self {
+        Self {
+            text: output.result.text.clone(),
+            confidence: output.result.confidence,
+            language: output.result.language.clone(),
+            x: output.capture.x,
+            y: output.capture.y,
+            blocks: output.result.blocks.clone(),
+        }
+    }
+}
+
+/// 命令行参数 `--config <路径>` 或 `--config=<路径>`
+fn config_arg(mut args: impl Iterator<Item = String>) -> Option<PathBuf> {
+    while let Some(arg) = args.next() {
+        if arg == "--config" {
+            return args.next().map(PathBuf::from);
+        }
+        if let Some(path) = arg.strip_prefix("--config=") {
+            return Some(PathBuf::from(path));
+        }
+    }
+    None
 }
 
 fn main() {
-    // 创建系统托盘菜单
-    let settings = CustomMenuItem::new("settings".to_string(), "设置");
-    let ocr_now = CustomMenuItem::new("ocr_now".to_string(), "立即识别");
-    let quit = CustomMenuItem::new("quit".to_string(), "退出");
-    
-    let tray_menu = SystemTrayMenu::new()
-        .add_item(ocr_now)
-        .add_native_item(tauri::SystemTrayMenuItem::Separator)
-        .add_item(settings)
-        .add_native_item(tauri::SystemTrayMenuItem::Separator)
-        .add_item(quit);
+    if let Some(path) = config_arg(std::env::args().skip(1)) {
+        if let Err(e) = AppConfig::set_config_path(&path) {
+            eprintln!("⚠️  {:#}", e);
+        }
+    }
+    match AppConfig::get_config_path() {
+        Ok(path) => println!("📁 配置文件: {:?}", path),
+        Err(e) => eprintln!("⚠️  {:#}", e),
+    }
     
-    let system_tray = SystemTray::new().with_menu(tray_menu);
-
     // 加载配置
     let initial_config = AppConfig::load().unwrap_or_else(|e| {
         eprintln!("⚠️  加载配置失败，使用默认配置: {}", e);
         AppConfig::default()
     });
     
+    // 创建系统托盘菜单
+    let system_tray = SystemTray::new().with_menu(tray_menu(&initial_config));
+    
     println!("📋 当前配置: {:?}", initial_config);
     
+    // 注册 OCR 引擎
+    let ocr_registry = OcrRegistry::with_default_backends();
+    ocr_registry.configure(&initial_config);
+    if let Err(e) = ocr_registry.get(initial_config.ocr_engine.as_str()) {
+        eprintln!("⚠️  {}", e);
+    }
+    
+    // 截图来源（可通过 SCREENOCR_SCREEN_SOURCE 使用图片代替真实屏幕）
+    let screen_source = screenshot::source_from_env().unwrap_or_else(|e| {
+        eprintln!("⚠️  截图来源配置无效，使用系统截图: {}", e);
+        Arc::new(screenshot::SystemScreenSource)
+    });
+    
+    // 打开历史数据库
+    let history = HistoryStore::default_path()
+        .and_then(|path| HistoryStore::open(&path))
+        .map(Arc::new)
+        .map_err(|e| eprintln!("⚠️  历史记录不可用: {}", e))
+        .ok();
+    
+    let pipeline = OcrPipeline::new(screen_source, ocr_registry);
+    if let Some(store) = &history {
+        pipeline.add_sink(Arc::new(HistorySink::new(store.clone())));
+    }
+    
     // 初始化热键处理器
     let mut hotkey_handler = HotKeyHandler::new()
         .expect("初始化热键管理器失败");
     
     // 注册初始热键
-    if let Err(e) = hotkey_handler.register(&initial_config.hotkey) {
+    if let Err(e) = hotkey_handler.register_all(
+        &initial_config.all_hotkey_bindings(),
+        initial_config.trigger_delay_ms,
+        initial_config.sequence_timeout_ms,
+    ) {
         eprintln!("⚠️  注册热键失败: {}", e);
     }
     
@@ -235,7 +843,18 @@ fn main() {
         .manage(AppState {
             config: Mutex::new(initial_config),
             hotkey_handler: Mutex::new(hotkey_handler),
+            pipeline,
+            jobs: JobManager::new(),
+            history,
+            last_region: Mutex::new(None),
+            live_mode: AtomicBool::new(false),
+            live_session: AtomicU64::new(0),
+            config_watcher: Mutex::new(None),
+            window_provider: window::provider_from_env(),
+            trigger_window: Mutex::new(None),
+            captures: CaptureCache::new(CAPTURE_CACHE_SIZE),
         })
+        .register_uri_scheme_protocol(CAPTURE_PROTOCOL, serve_capture)
         .system_tray(system_tray)
         .on_system_tray_event(|app, event| match event {
             SystemTrayEvent::LeftClick { .. } => {
@@ -253,67 +872,102 @@ fn main() {
                     }
                 }
                 "ocr_now" => {
-                    // 触发 OCR 识别
-                    let app_handle = app.clone();
-                    tauri::async_runtime::spawn(async move {
-                        match perform_ocr_on_screen(
-                            app_handle.clone(),
-                            app_handle.state::<AppState>(),
-                        ).await {
-                            Ok(result) => {
-                                println!("✅ OCR 成功: {} 个字符", result.text.len());
-                                // 可以发送通知或显示结果窗口
-                            }
-                            Err(e) => {
-                                eprintln!("❌ OCR 失败: {}", e);
-                            }
-                        }
-                    });
+                    // 与全屏识别热键相同：结果通过 ocr-result 事件发送到前端
+                    let trigger = HotkeyTrigger {
+                        action: HotkeyAction::FullScreenOcr,
+                        delay_elapsed: false,
+                    };
+                    tauri::async_runtime::spawn(run_hotkey_action(app.clone(), trigger));
                 }
                 "quit" => {
                     std::process::exit(0);
                 }
-                _ => {}
+                id => {
+                    let index = id.strip_prefix(PROFILE_MENU_PREFIX).and_then(|index| index.parse().ok());
+                    if let Some(index) = index {
+                        if let Err(e) = activate_profile_at(app, index) {
+                            eprintln!("⚠️  切换方案失败: {}", e);
+                        }
+                    }
+                }
             },
             _ => {}
         })
         .setup(|app| {
             // 启动热键监听
             let app_handle = app.handle();
-            HotKeyHandler::start_listener(app_handle.clone());
+            
+            // 识别结果的剪贴板和前端事件输出
+            let pipeline = &app.state::<AppState>().pipeline;
+            pipeline.add_sink(Arc::new(ClipboardSink(app_handle.clone())));
+            pipeline.add_sink(Arc::new(EventSink(app_handle.clone())));
+            
+            // 识别进度通过 ocr-progress 事件发送到前端
+            let progress_handle = app_handle.clone();
+            app.state::<AppState>().jobs.set_progress_listener(move |progress| {
+                let _ = progress_handle.emit_all("ocr-progress", progress);
+            });
+            
+            app.state::<AppState>()
+                .hotkey_handler
+                .lock()
+                .unwrap()
+                .start_listener(app_handle.clone());
             
             // 监听热键触发事件
             let app_handle_clone = app_handle.clone();
-            app.listen_global("hotkey-triggered", move |_event| {
-                let app_clone = app_handle_clone.clone();
-                tauri::async_runtime::spawn(async move {
-                    println!("🎯 热键触发，开始 OCR...");
-                    match perform_ocr_on_screen(
-                        app_clone.clone(),
-                        app_clone.state::<AppState>(),
-                    ).await {
-                        Ok(result) => {
-                            println!("✅ OCR 成功: {} 个字符", result.text.len());
-                            // 发送结果到前端
-                            let _ = app_clone.emit_all("ocr-result", result);
-                        }
-                        Err(e) => {
-                            eprintln!("❌ OCR 失败: {}", e);
-                            let _ = app_clone.emit_all("ocr-error", e);
-                        }
+            app.listen_global("hotkey-triggered", move |event| {
+                let trigger = match event
+                    .payload()
+                    .and_then(|payload| serde_json::from_str::<HotkeyTrigger>(payload).ok())
+                {
+                    Some(trigger) => trigger,
+                    None => {
+                        eprintln!("⚠️  无法解析热键事件: {:?}", event.payload());
+                        return;
                     }
-                });
+                };
+                let app_clone = app_handle_clone.clone();
+                tauri::async_runtime::spawn(run_hotkey_action(app_clone, trigger));
             });
             
+            // 监听配置文件的手动修改
+            let reload_handle = app_handle.clone();
+            match AppConfig::get_config_path().and_then(|path| {
+                ConfigWatcher::start(path, move |result| reload_config(&reload_handle, result))
+            }) {
+                Ok(watcher) => *app.state::<AppState>().config_watcher.lock().unwrap() = Some(watcher),
+                Err(e) => eprintln!("⚠️  配置文件热加载不可用: {:#}", e),
+            }
+            
             Ok(())
         })
         .invoke_handler(tauri::generate_handler![
+            set_locale,
             get_config,
             update_config,
             reset_config,
+            export_config,
+            import_config,
+            get_config_path,
+            create_profile,
+            rename_profile,
+            delete_profile,
+            duplicate_profile,
+            activate_profile,
+            check_hotkey,
+            normalize_hotkey,
+            list_ocr_engines,
             capture_screen,
             perform_ocr_on_screen,
-            perform_ocr_on_region
+            perform_ocr_on_region,
+            cancel_region_select,
+            cancel_ocr,
+            list_history,
+            search_history,
+            pin_history_entry,
+            delete_history_entry,
+            purge_history
         ])
         .run(tauri::generate_context!())
         .expect("error while running tauri application");
@@ -1,12 +1,14 @@
 use anyhow::Result;
+#[cfg(feature = "tesseract")]
+use anyhow::Context;
+use async_trait::async_trait;
+use std::sync::Arc;
+use crate::config::AppConfig;
 use crate::screenshot::ScreenCapture;
 
-/// OCR 引擎类型
-#[derive(Debug, Clone, serde::Deserialize, serde::Serialize)]
-pub enum OcrEngine {
-    Tesseract,
-    WeChatOCR,
-}
+pub mod onnx;
+
+pub use onnx::PaddleOcrBackend;
 
 /// OCR 识别结果
 #[derive(Debug, Clone, serde::Serialize)]
@@ -14,31 +16,531 @@ pub struct OcrResult {
     pub text: String,
     pub confidence: f32,
     pub language: String,
+    /// 文本块 → 行 → 单词的层级结构，坐标相对于截图左上角
+    pub blocks: Vec<OcrBlock>,
 }
 
-/// 执行 OCR 识别
-pub async fn perform_ocr(capture: &ScreenCapture, engine: OcrEngine) -> Result<OcrResult> {
-    match engine {
-        OcrEngine::Tesseract => perform_tesseract_ocr(capture).await,
-        OcrEngine::WeChatOCR => perform_wechat_ocr(capture).await,
+/// 像素边界框，坐标相对于 `ScreenCapture` 的原点（`x`/`y`）
+#[derive(Debug, Clone, Copy, PartialEq, Eq, serde::Serialize)]
+pub struct BoundingBox {
+    pub x: u32,
+    pub y: u32,
+    pub width: u32,
+    pub height: u32,
+}
+
+impl BoundingBox {
+    /// 同时包含两个边界框的最小边界框
+    pub fn union(&self, other: &BoundingBox) -> BoundingBox {
+        let left = self.x.min(other.x);
+        let top = self.y.min(other.y);
+        let right = (self.x + self.width).max(other.x + other.width);
+        let bottom = (self.y + self.height).max(other.y + other.height);
+        BoundingBox {
+            x: left,
+            y: top,
+            width: right - left,
+            height: bottom - top,
+        }
+    }
+    
+    /// 坐标整体缩小 `factor` 倍（用于把放大后图像上的坐标映射回原图）
+    pub fn scaled_down(&self, factor: u32) -> BoundingBox {
+        let factor = factor.max(1);
+        BoundingBox {
+            x: self.x / factor,
+            y: self.y / factor,
+            width: self.width.div_ceil(factor),
+            height: self.height.div_ceil(factor),
+        }
+    }
+    
+    /// 绕 `center` 旋转 `degrees` 度（顺时针为正）后的外接矩形，超出左上边界的部分被截掉
+    pub fn rotated(&self, degrees: f32, center: (f32, f32)) -> BoundingBox {
+        let (sin, cos) = degrees.to_radians().sin_cos();
+        let corners = [
+            (self.x as f32, self.y as f32),
+            ((self.x + self.width) as f32, self.y as f32),
+            (self.x as f32, (self.y + self.height) as f32),
+            ((self.x + self.width) as f32, (self.y + self.height) as f32),
+        ];
+        let rotated = corners.map(|(x, y)| {
+            let (dx, dy) = (x - center.0, y - center.1);
+            (dx * cos - dy * sin + center.0, dx * sin + dy * cos + center.1)
+        });
+        
+        let left = rotated.iter().map(|p| p.0).fold(f32::INFINITY, f32::min).max(0.0).round() as u32;
+        let top = rotated.iter().map(|p| p.1).fold(f32::INFINITY, f32::min).max(0.0).round() as u32;
+        let right = rotated.iter().map(|p| p.0).fold(f32::NEG_INFINITY, f32::max).max(0.0).round() as u32;
+        let bottom = rotated.iter().map(|p| p.1).fold(f32::NEG_INFINITY, f32::max).max(0.0).round() as u32;
+        BoundingBox {
+            x: left,
+            y: top,
+            width: (right - left).max(1),
+            height: (bottom - top).max(1),
+        }
+    }
+}
+
+/// 单词
+#[derive(Debug, Clone, serde::Serialize)]
+pub struct OcrWord {
+    pub text: String,
+    pub bbox: BoundingBox,
+    pub confidence: f32,
+}
+
+/// 文本行
+#[derive(Debug, Clone, serde::Serialize)]
+pub struct OcrLine {
+    pub text: String,
+    pub bbox: BoundingBox,
+    pub words: Vec<OcrWord>,
+}
+
+/// 文本块
+#[derive(Debug, Clone, serde::Serialize)]
+pub struct OcrBlock {
+    pub text: String,
+    pub bbox: BoundingBox,
+    pub lines: Vec<OcrLine>,
+}
+
+impl OcrResult {
+    /// 由层级结构生成结果，文本按行拼接，置信度取所有单词的平均值
+    pub fn from_blocks(blocks: Vec<OcrBlock>, language: &str) -> Self {
+        let text = blocks
+            .iter()
+            .map(|block| block.text.as_str())
+            .collect::<Vec<_>>()
+            .join("\n\n");
+        
+        let confidences: Vec<f32> = blocks
+            .iter()
+            .flat_map(|block| &block.lines)
+            .flat_map(|line| &line.words)
+            .map(|word| word.confidence)
+            .collect();
+        let confidence = if confidences.is_empty() {
+            0.0
+        } else {
+            confidences.iter().sum::<f32>() / confidences.len() as f32
+        };
+        
+        Self {
+            text,
+            confidence,
+            language: language.to_string(),
+            blocks,
+        }
+    }
+    
+    /// 预处理放大了图像时，把所有边界框映射回原图坐标
+    pub fn unscale(&mut self, factor: u32) {
+        if factor <= 1 {
+            return;
+        }
+        self.map_boxes(|bbox| bbox.scaled_down(factor));
+    }
+    
+    /// 对所有层级的边界框执行同一个变换
+    pub fn map_boxes(&mut self, f: impl Fn(&BoundingBox) -> BoundingBox) {
+        for block in &mut self.blocks {
+            block.bbox = f(&block.bbox);
+            for line in &mut block.lines {
+                line.bbox = f(&line.bbox);
+                for word in &mut line.words {
+                    word.bbox = f(&word.bbox);
+                }
+            }
+        }
+    }
+}
+
+/// OCR 引擎能力描述
+#[derive(Debug, Clone, serde::Serialize)]
+pub struct BackendCapabilities {
+    /// 当前平台和构建是否可用
+    pub available: bool,
+    /// 是否完全离线运行
+    pub offline: bool,
+    /// 是否支持同时识别多种语言（例如 "chi_sim+eng"）
+    pub multi_language: bool,
+    /// 是否输出单词级边界框
+    pub word_boxes: bool,
+}
+
+/// OCR 引擎接口，新增引擎只需实现该 trait 并注册到 [`OcrRegistry`]
+#[async_trait]
+pub trait OcrBackend: Send + Sync {
+    /// 引擎名称，对应 `AppConfig.ocr_engine`
+    fn name(&self) -> &str;
+    
+    /// 支持的语言列表
+    fn supported_languages(&self) -> Vec<String>;
+    
+    /// 引擎能力
+    fn capabilities(&self) -> BackendCapabilities;
+    
+    /// 配置变化时调用，引擎可从中读取自己的设置（例如模型目录）
+    fn configure(&self, _config: &AppConfig) {}
+    
+    /// 识别语言中缺少语言包的部分，无法检测时返回空列表
+    fn missing_languages(&self, _language: &str) -> Vec<String> {
+        Vec::new()
+    }
+    
+    /// 识别截图中的文字
+    async fn recognize(&self, capture: &ScreenCapture, language: &str) -> Result<OcrResult>;
+}
+
+/// 引擎信息（发送给前端）
+#[derive(Debug, Clone, serde::Serialize)]
+pub struct OcrEngineInfo {
+    pub name: String,
+    pub languages: Vec<String>,
+    pub capabilities: BackendCapabilities,
+}
+
+/// OCR 引擎注册表
+#[derive(Clone, Default)]
+pub struct OcrRegistry {
+    backends: Vec<Arc<dyn OcrBackend>>,
+}
+
+impl OcrRegistry {
+    pub fn new() -> Self {
+        Self::default()
+    }
+    
+    /// 创建包含所有内置引擎的注册表
+    pub fn with_default_backends() -> Self {
+        let mut registry = Self::new();
+        registry.register(Arc::new(TesseractBackend));
+        registry.register(Arc::new(WeChatOcrBackend));
+        registry.register(Arc::new(PaddleOcrBackend::new()));
+        registry
+    }
+    
+    /// 把配置下发给所有引擎
+    pub fn configure(&self, config: &AppConfig) {
+        for backend in &self.backends {
+            backend.configure(config);
+        }
+    }
+    
+    /// 注册引擎，同名引擎会被替换
+    pub fn register(&mut self, backend: Arc<dyn OcrBackend>) {
+        self.backends.retain(|b| b.name() != backend.name());
+        self.backends.push(backend);
+    }
+    
+    /// 按名称查找引擎，未知名称返回错误
+    pub fn get(&self, name: &str) -> Result<Arc<dyn OcrBackend>> {
+        self.backends
+            .iter()
+            .find(|b| b.name() == name)
+            .cloned()
+            .ok_or_else(|| {
+                anyhow::anyhow!("未知的 OCR 引擎: {}（可用引擎: {}）", name, self.names().join(", "))
+            })
+    }
+    
+    /// 已注册的引擎名称
+    pub fn names(&self) -> Vec<String> {
+        self.backends.iter().map(|b| b.name().to_string()).collect()
+    }
+    
+    /// 所有引擎的详细信息
+    pub fn describe(&self) -> Vec<OcrEngineInfo> {
+        self.backends
+            .iter()
+            .map(|b| OcrEngineInfo {
+                name: b.name().to_string(),
+                languages: b.supported_languages(),
+                capabilities: b.capabilities(),
+            })
+            .collect()
+    }
+}
+
+/// Tesseract 引擎
+pub struct TesseractBackend;
+
+#[async_trait]
+impl OcrBackend for TesseractBackend {
+    fn name(&self) -> &str {
+        "Tesseract"
+    }
+    
+    fn supported_languages(&self) -> Vec<String> {
+        let installed = installed_tesseract_languages();
+        if installed.is_empty() {
+            vec!["chi_sim".to_string(), "eng".to_string()]
+        } else {
+            installed
+        }
+    }
+    
+    fn capabilities(&self) -> BackendCapabilities {
+        BackendCapabilities {
+            available: cfg!(feature = "tesseract"),
+            offline: true,
+            multi_language: true,
+            word_boxes: true,
+        }
+    }
+    
+    fn missing_languages(&self, language: &str) -> Vec<String> {
+        missing_languages(language, &installed_tesseract_languages())
+    }
+    
+    async fn recognize(&self, capture: &ScreenCapture, language: &str) -> Result<OcrResult> {
+        perform_tesseract_ocr(capture, language).await
+    }
+}
+
+/// `chi_sim+eng` 形式的语言中没有安装的部分，找不到 tessdata 目录时不做检查
+fn missing_languages(language: &str, installed: &[String]) -> Vec<String> {
+    if installed.is_empty() {
+        return Vec::new();
+    }
+    language
+        .split('+')
+        .map(str::trim)
+        .filter(|lang| !lang.is_empty() && !installed.iter().any(|i| i == lang))
+        .map(str::to_string)
+        .collect()
+}
+
+/// 扫描 tessdata 目录，列出已安装的语言包
+fn installed_tesseract_languages() -> Vec<String> {
+    let mut dirs: Vec<std::path::PathBuf> = Vec::new();
+    if let Ok(prefix) = std::env::var("TESSDATA_PREFIX") {
+        dirs.push(prefix.into());
+    }
+    dirs.push("/usr/share/tessdata".into());
+    dirs.push("/usr/share/tesseract-ocr/5/tessdata".into());
+    dirs.push("/usr/share/tesseract-ocr/4.00/tessdata".into());
+    dirs.push("/usr/local/share/tessdata".into());
+    
+    let mut languages: Vec<String> = dirs
+        .iter()
+        .filter_map(|dir| std::fs::read_dir(dir).ok())
+        .flatten()
+        .filter_map(|entry| entry.ok())
+        .filter_map(|entry| {
+            let name = entry.file_name().to_string_lossy().to_string();
+            name.strip_suffix(".traineddata").map(|lang| lang.to_string())
+        })
+        .filter(|lang| lang != "osd")
+        .collect();
+    
+    languages.sort();
+    languages.dedup();
+    languages
+}
+
+/// WeChatOCR 引擎（仅 Windows）
+pub struct WeChatOcrBackend;
+
+#[async_trait]
+impl OcrBackend for WeChatOcrBackend {
+    fn name(&self) -> &str {
+        "WeChatOCR"
+    }
+    
+    fn supported_languages(&self) -> Vec<String> {
+        vec!["zh-CN".to_string(), "en".to_string()]
+    }
+    
+    fn capabilities(&self) -> BackendCapabilities {
+        BackendCapabilities {
+            available: cfg!(target_os = "windows"),
+            offline: true,
+            multi_language: true,
+            word_boxes: false,
+        }
+    }
+    
+    async fn recognize(&self, capture: &ScreenCapture, _language: &str) -> Result<OcrResult> {
+        perform_wechat_ocr(capture).await
     }
 }
 
 /// 使用 Tesseract 进行 OCR
-async fn perform_tesseract_ocr(_capture: &ScreenCapture) -> Result<OcrResult> {
-    // TODO: 实现 Tesseract OCR
-    // 需要系统安装 Tesseract 并添加 tesseract crate
+#[cfg(feature = "tesseract")]
+async fn perform_tesseract_ocr(capture: &ScreenCapture, language: &str) -> Result<OcrResult> {
+    // Tesseract 是同步阻塞调用，放到阻塞线程池中执行
+    let capture = capture.clone();
+    let language = language.to_string();
     
-    // 模拟识别结果
-    tokio::time::sleep(tokio::time::Duration::from_millis(500)).await;
+    tokio::task::spawn_blocking(move || recognize_with_tesseract(&capture, &language))
+        .await
+        .context("Tesseract 识别任务异常退出")?
+}
+
+/// 直接把 RGBA 缓冲区交给 Tesseract 识别（不经过临时文件）
+#[cfg(feature = "tesseract")]
+fn recognize_with_tesseract(capture: &ScreenCapture, language: &str) -> Result<OcrResult> {
+    use tesseract::Tesseract;
+    
+    let width = i32::try_from(capture.width).context("图像宽度过大")?;
+    let height = i32::try_from(capture.height).context("图像高度过大")?;
+    
+    if capture.data.len() != capture.width as usize * capture.height as usize * 4 {
+        return Err(anyhow::anyhow!(
+            "图像数据长度与尺寸不匹配: {} 字节, {}x{}",
+            capture.data.len(),
+            capture.width,
+            capture.height
+        ));
+    }
+    
+    let mut tess = Tesseract::new(None, Some(language))
+        .with_context(|| format!("初始化 Tesseract 失败，请确认已安装语言包: {}", language))?
+        .set_frame(&capture.data, width, height, 4, width * 4)
+        .context("向 Tesseract 传入图像失败")?
+        .recognize()
+        .context("Tesseract 识别失败")?;
+    
+    let text = tess.get_text().context("读取识别文本失败")?;
+    // mean_text_conf 返回 0-100，没有识别到文字时可能为负数
+    let confidence = tess.mean_text_conf().clamp(0, 100) as f32 / 100.0;
+    let tsv = tess.get_tsv_text(0).context("读取文字位置信息失败")?;
     
     Ok(OcrResult {
-        text: "Tesseract OCR 功能开发中...\n请安装 Tesseract 引擎\n\n示例识别文本：\nHello World\n你好世界".to_string(),
-        confidence: 0.85,
-        language: "chi_sim+eng".to_string(),
+        text,
+        confidence,
+        language: language.to_string(),
+        blocks: parse_tesseract_tsv(&tsv),
     })
 }
 
+/// 解析 Tesseract TSV 输出为 块 → 行 → 单词 结构
+///
+/// 每行的列依次为: level page_num block_num par_num line_num word_num left top width height conf text，
+/// 其中 level 2 为文本块，4 为文本行，5 为单词。
+pub fn parse_tesseract_tsv(tsv: &str) -> Vec<OcrBlock> {
+    let mut blocks: Vec<(u32, OcrBlock)> = Vec::new();
+    // 行的 key 为 (block_num, par_num, line_num)
+    let mut lines: Vec<((u32, u32, u32), OcrLine)> = Vec::new();
+    
+    for row in tsv.lines() {
+        let cols: Vec<&str> = row.split('\t').collect();
+        if cols.len() < 11 {
+            continue;
+        }
+        
+        let parsed: Result<Vec<f64>, _> = cols[..11].iter().map(|c| c.trim().parse::<f64>()).collect();
+        let nums: Vec<i64> = match parsed {
+            Ok(values) => values.into_iter().map(|v| v as i64).collect(),
+            // 表头或格式错误的行
+            Err(_) => continue,
+        };
+        
+        let level = nums[0];
+        let key = (nums[2] as u32, nums[3] as u32, nums[4] as u32);
+        let bbox = BoundingBox {
+            x: nums[6].max(0) as u32,
+            y: nums[7].max(0) as u32,
+            width: nums[8].max(0) as u32,
+            height: nums[9].max(0) as u32,
+        };
+        let text = cols.get(11).map(|t| t.trim()).unwrap_or("");
+        
+        match level {
+            2 => blocks.push((
+                key.0,
+                OcrBlock {
+                    text: String::new(),
+                    bbox,
+                    lines: Vec::new(),
+                },
+            )),
+            4 => lines.push((
+                key,
+                OcrLine {
+                    text: String::new(),
+                    bbox,
+                    words: Vec::new(),
+                },
+            )),
+            5 if !text.is_empty() => {
+                let word = OcrWord {
+                    text: text.to_string(),
+                    bbox,
+                    confidence: (nums[10].clamp(0, 100) as f32) / 100.0,
+                };
+                match lines.iter_mut().find(|(k, _)| *k == key) {
+                    Some((_, line)) => {
+                        line.bbox = line.bbox.union(&bbox);
+                        line.words.push(word);
+                    }
+                    None => lines.push((
+                        key,
+                        OcrLine {
+                            text: String::new(),
+                            bbox,
+                            words: vec![word],
+                        },
+                    )),
+                }
+            }
+            _ => {}
+        }
+    }
+    
+    for ((block_num, _, _), mut line) in lines {
+        if line.words.is_empty() {
+            continue;
+        }
+        line.text = line
+            .words
+            .iter()
+            .map(|w| w.text.as_str())
+            .collect::<Vec<_>>()
+            .join(" ");
+        
+        match blocks.iter_mut().find(|(num, _)| *num == block_num) {
+            Some((_, block)) => {
+                block.bbox = block.bbox.union(&line.bbox);
+                block.lines.push(line);
+            }
+            None => blocks.push((
+                block_num,
+                OcrBlock {
+                    text: String::new(),
+                    bbox: line.bbox,
+                    lines: vec![line],
+                },
+            )),
+        }
+    }
+    
+    blocks
+        .into_iter()
+        .map(|(_, mut block)| {
+            block.text = block
+                .lines
+                .iter()
+                .map(|l| l.text.as_str())
+                .collect::<Vec<_>>()
+                .join("\n");
+            block
+        })
+        .filter(|block| !block.lines.is_empty())
+        .collect()
+}
+
+/// 未启用 `tesseract` 特性时的占位实现
+#[cfg(not(feature = "tesseract"))]
+async fn perform_tesseract_ocr(_capture: &ScreenCapture, _language: &str) -> Result<OcrResult> {
+    Err(anyhow::anyhow!(
+        "当前构建未启用 Tesseract 支持，请安装 Tesseract 后使用 `--features tesseract` 重新编译"
+    ))
+}
+
 /// 使用 WeChatOCR 进行 OCR
 async fn perform_wechat_ocr(_capture: &ScreenCapture) -> Result<OcrResult> {
     // TODO: 实现 WeChatOCR
@@ -51,64 +553,113 @@ async fn perform_wechat_ocr(_capture: &ScreenCapture) -> Result<OcrResult> {
         text: "WeChatOCR 功能开发中...\n需要安装微信\n\n示例识别文本：\nScreen OCR\n屏幕文字识别".to_string(),
         confidence: 0.90,
         language: "zh-CN".to_string(),
+        blocks: Vec::new(),
     })
 }
 
-// TODO: 实现真实的 Tesseract OCR
-// 示例代码（需要安装 Tesseract 和取消注释 tesseract crate）:
-/*
-use tesseract::Tesseract;
-
-async fn perform_tesseract_ocr_real(capture: &ScreenCapture) -> Result<OcrResult> {
-    // 将截图转换为图像
-    let img = image::ImageBuffer::from_raw(
-        capture.width,
-        capture.height,
-        capture.data.clone()
-    ).ok_or_else(|| anyhow::anyhow!("无法创建图像"))?;
-    
-    // 保存临时文件
-    let temp_path = std::env::temp_dir().join("ocr_temp.png");
-    img.save(&temp_path)?;
-    
-    // 执行 OCR
-    let mut tess = Tesseract::new(None, Some("chi_sim+eng"))?;
-    tess.set_image(temp_path.to_str().unwrap())?;
-    let text = tess.get_text()?;
-    let confidence = tess.mean_text_conf() as f32 / 100.0;
-    
-    // 删除临时文件
-    std::fs::remove_file(temp_path)?;
-    
-    Ok(OcrResult {
-        text,
-        confidence,
-        language: "chi_sim+eng".to_string(),
-    })
-}
-*/
-
 #[cfg(test)]
 mod tests {
     use super::*;
     use crate::screenshot::ScreenCapture;
 
+    fn blank_capture() -> ScreenCapture {
+        ScreenCapture::new(100, 100, vec![255; 100 * 100 * 4], 0, 0)
+    }
+
+    #[test]
+    fn test_registry_rejects_unknown_engine() {
+        let registry = OcrRegistry::with_default_backends();
+        assert!(registry.get("Tesseract").is_ok());
+        assert!(registry.get("WeChatOCR").is_ok());
+        assert!(registry.get("PaddleOCR").is_ok());
+        
+        let err = registry.get("NoSuchEngine").err().unwrap();
+        assert!(err.to_string().contains("NoSuchEngine"));
+    }
+
+    #[test]
+    fn test_registry_replaces_same_name() {
+        let mut registry = OcrRegistry::with_default_backends();
+        registry.register(Arc::new(TesseractBackend));
+        assert_eq!(registry.names(), vec!["WeChatOCR", "PaddleOCR", "Tesseract"]);
+    }
+
+    #[test]
+    fn test_missing_languages() {
+        let installed = vec!["chi_sim".to_string(), "eng".to_string()];
+        assert!(missing_languages("chi_sim+eng", &installed).is_empty());
+        assert_eq!(missing_languages("eng+jpn+kor", &installed), vec!["jpn", "kor"]);
+        // 找不到语言包目录时交给 Tesseract 自己报错
+        assert!(missing_languages("jpn", &[]).is_empty());
+    }
+
+    #[test]
+    fn test_parse_tesseract_tsv() {
+        let tsv = "level\tpage_num\tblock_num\tpar_num\tline_num\tword_num\tleft\ttop\twidth\theight\tconf\ttext\n\
+1\t1\t0\t0\t0\t0\t0\t0\t200\t100\t-1\t\n\
+2\t1\t1\t0\t0\t0\t10\t10\t120\t40\t-1\t\n\
+3\t1\t1\t1\t0\t0\t10\t10\t120\t40\t-1\t\n\
+4\t1\t1\t1\t1\t0\t10\t10\t120\t15\t-1\t\n\
+5\t1\t1\t1\t1\t1\t10\t10\t50\t15\t96.5\tHello\n\
+5\t1\t1\t1\t1\t2\t70\t10\t60\t15\t90\tWorld\n\
+4\t1\t1\t1\t2\t0\t10\t35\t80\t15\t-1\t\n\
+5\t1\t1\t1\t2\t1\t10\t35\t80\t15\t80\t你好世界\n";
+        
+        let blocks = parse_tesseract_tsv(tsv);
+        assert_eq!(blocks.len(), 1);
+        assert_eq!(blocks[0].text, "Hello World\n你好世界");
+        assert_eq!(blocks[0].lines.len(), 2);
+        
+        let word = &blocks[0].lines[0].words[1];
+        assert_eq!(word.text, "World");
+        assert_eq!(word.bbox, BoundingBox { x: 70, y: 10, width: 60, height: 15 });
+        assert!((word.confidence - 0.9).abs() < f32::EPSILON);
+    }
+
+    #[test]
+    fn test_result_from_blocks() {
+        let bbox = BoundingBox { x: 0, y: 0, width: 10, height: 10 };
+        let word = |text: &str, confidence: f32| OcrWord { text: text.to_string(), bbox, confidence };
+        let blocks = vec![OcrBlock {
+            text: "a b".to_string(),
+            bbox,
+            lines: vec![OcrLine {
+                text: "a b".to_string(),
+                bbox,
+                words: vec![word("a", 1.0), word("b", 0.5)],
+            }],
+        }];
+        
+        let result = OcrResult::from_blocks(blocks, "eng");
+        assert_eq!(result.text, "a b");
+        assert!((result.confidence - 0.75).abs() < f32::EPSILON);
+    }
+
+    #[cfg(not(feature = "tesseract"))]
     #[tokio::test]
-    async fn test_tesseract_ocr() {
-        let capture = ScreenCapture {
-            width: 100,
-            height: 100,
-            data: vec![0; 100 * 100 * 4],
-            x: 0,
-            y: 0,
-        };
+    async fn test_tesseract_disabled() {
+        let result = perform_tesseract_ocr(&blank_capture(), "eng").await;
+        assert!(result.is_err());
+    }
 
-        let result = perform_tesseract_ocr(&capture).await;
+    #[cfg(feature = "tesseract")]
+    #[tokio::test]
+    async fn test_tesseract_blank_image() {
+        let result = perform_tesseract_ocr(&blank_capture(), "eng").await;
         assert!(result.is_ok());
         
         let ocr_result = result.unwrap();
-        assert!(!ocr_result.text.is_empty());
-        assert!(ocr_result.confidence > 0.0);
+        assert!(ocr_result.text.trim().is_empty());
+        assert_eq!(ocr_result.language, "eng");
     }
-}
 
+    #[cfg(feature = "tesseract")]
+    #[tokio::test]
+    async fn test_tesseract_rejects_mismatched_buffer() {
+        let mut capture = blank_capture();
+        Arc::make_mut(&mut capture.data).truncate(10);
+        
+        let result = perform_tesseract_ocr(&capture, "eng").await;
+        assert!(result.is_err());
+    }
+}
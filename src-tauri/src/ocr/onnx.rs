@@ -0,0 +1,610 @@
+//! 基于 ONNX Runtime 的 PaddleOCR 风格离线引擎（CPU 推理）
+//!
+//! 模型目录需包含:
+//! - `det.onnx`: DB 文本检测模型，输出 1x1xHxW 概率图
+//! - `rec.onnx`: CRNN/SVTR 文本识别模型，输出 1xTxC 概率序列
+//! - `ppocr_keys.txt`: 识别字典，每行一个字符
+
+use anyhow::Result;
+use async_trait::async_trait;
+use image::{imageops, imageops::FilterType, RgbaImage};
+use std::path::{Path, PathBuf};
+use std::sync::RwLock;
+
+use super::{BackendCapabilities, BoundingBox, OcrBackend, OcrBlock, OcrLine, OcrResult, OcrWord};
+use crate::config::AppConfig;
+use crate::screenshot::{CaptureView, ScreenCapture};
+
+pub const DET_MODEL_FILE: &str = "det.onnx";
+pub const REC_MODEL_FILE: &str = "rec.onnx";
+pub const DICT_FILE: &str = "ppocr_keys.txt";
+
+/// 检测输入的最长边
+#[cfg(feature = "onnx")]
+const DET_LIMIT_SIDE: u32 = 960;
+/// 概率图二值化阈值
+const DET_THRESHOLD: f32 = 0.3;
+/// 文本框平均得分阈值
+const DET_BOX_THRESHOLD: f32 = 0.6;
+/// 文本框外扩比例
+const DET_UNCLIP_RATIO: f32 = 1.5;
+/// 过小的连通域视为噪点
+const DET_MIN_AREA: usize = 9;
+/// 识别输入高度
+const REC_HEIGHT: u32 = 48;
+/// 识别输入的最小宽度
+const REC_MIN_WIDTH: u32 = 320;
+
+/// PaddleOCR 引擎
+pub struct PaddleOcrBackend {
+    model_dir: RwLock<PathBuf>,
+    #[cfg(feature = "onnx")]
+    /// 已加载的模型，在识别线程中加载
+    models: std::sync::Arc<std::sync::Mutex<Option<std::sync::Arc<models::PaddleModels>>>>,
+}
+
+impl PaddleOcrBackend {
+    pub fn new() -> Self {
+        Self {
+            model_dir: RwLock::new(default_model_dir()),
+            #[cfg(feature = "onnx")]
+            models: Default::default(),
+        }
+    }
+
+    fn model_dir(&self) -> PathBuf {
+        self.model_dir.read().unwrap().clone()
+    }
+}
+
+impl Default for PaddleOcrBackend {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// 默认模型目录: `<数据目录>/screenocr-tauri/models/paddleocr`
+pub fn default_model_dir() -> PathBuf {
+    dirs::data_dir()
+        .unwrap_or_else(std::env::temp_dir)
+        .join("screenocr-tauri")
+        .join("models")
+        .join("paddleocr")
+}
+
+/// 模型文件是否齐全
+fn models_present(dir: &Path) -> bool {
+    [DET_MODEL_FILE, REC_MODEL_FILE, DICT_FILE]
+        .iter()
+        .all(|file| dir.join(file).is_file())
+}
+
+#[async_trait]
+impl OcrBackend for PaddleOcrBackend {
+    fn name(&self) -> &str {
+        "PaddleOCR"
+    }
+
+    fn supported_languages(&self) -> Vec<String> {
+        // 识别语言由模型和字典决定，默认的中文模型同时支持中英文
+        vec!["chi_sim+eng".to_string()]
+    }
+
+    fn capabilities(&self) -> BackendCapabilities {
+        BackendCapabilities {
+            available: cfg!(feature = "onnx") && models_present(&self.model_dir()),
+            offline: true,
+            multi_language: true,
+            word_boxes: true,
+        }
+    }
+
+    fn configure(&self, config: &AppConfig) {
+        let dir = match config.onnx_model_dir.as_deref() {
+            Some(dir) if !dir.trim().is_empty() => PathBuf::from(dir),
+            _ => default_model_dir(),
+        };
+
+        let mut current = self.model_dir.write().unwrap();
+        if *current != dir {
+            *current = dir;
+            // 目录变化后在下次识别时重新加载模型
+            #[cfg(feature = "onnx")]
+            {
+                *self.models.lock().unwrap() = None;
+            }
+        }
+    }
+
+    #[cfg(feature = "onnx")]
+    async fn recognize(&self, capture: &ScreenCapture, language: &str) -> Result<OcrResult> {
+        use anyhow::Context;
+
+        let models = self.models.clone();
+        let model_dir = self.model_dir();
+        let capture = capture.clone();
+        let language = language.to_string();
+        // 首次识别时加载模型需要数秒，放在阻塞线程中，识别超时和取消不必等待加载完成
+        tokio::task::spawn_blocking(move || {
+            let models = {
+                let mut cached = models.lock().unwrap();
+                match cached.as_ref() {
+                    Some(models) => models.clone(),
+                    None => {
+                        let loaded = std::sync::Arc::new(models::PaddleModels::load(&model_dir)?);
+                        *cached = Some(loaded.clone());
+                        loaded
+                    }
+                }
+            };
+            let image = capture.view()?;
+            let lines = models.run(&image)?;
+            Ok(OcrResult::from_blocks(group_lines_into_blocks(lines), &language))
+        })
+        .await
+        .context("PaddleOCR 识别任务异常退出")?
+    }
+
+    #[cfg(not(feature = "onnx"))]
+    async fn recognize(&self, _capture: &ScreenCapture, _language: &str) -> Result<OcrResult> {
+        Err(anyhow::anyhow!(
+            "当前构建未启用 PaddleOCR 支持，请使用 `--features onnx` 重新编译"
+        ))
+    }
+}
+
+#[cfg(feature = "onnx")]
+mod models {
+    use super::*;
+    use anyhow::Context;
+    use ort::session::Session;
+    use ort::value::Tensor;
+
+    /// 已加载的检测、识别模型与字典
+    pub struct PaddleModels {
+        det: Session,
+        rec: Session,
+        dictionary: Vec<String>,
+    }
+
+    impl PaddleModels {
+        pub fn load(dir: &Path) -> Result<Self> {
+            if !models_present(dir) {
+                return Err(anyhow::anyhow!(
+                    "PaddleOCR 模型文件不完整，请在 {:?} 放置 {}、{} 和 {}",
+                    dir,
+                    DET_MODEL_FILE,
+                    REC_MODEL_FILE,
+                    DICT_FILE
+                ));
+            }
+
+            let threads = std::thread::available_parallelism()
+                .map(|n| n.get())
+                .unwrap_or(4);
+            let open = |file: &str| -> Result<Session> {
+                Session::builder()?
+                    .with_intra_threads(threads)?
+                    .commit_from_file(dir.join(file))
+                    .with_context(|| format!("加载模型失败: {:?}", dir.join(file)))
+            };
+
+            let dictionary = load_dictionary(&std::fs::read_to_string(dir.join(DICT_FILE))
+                .context("读取识别字典失败")?);
+
+            eprintln!("✅ PaddleOCR 模型已加载: {:?}", dir);
+            Ok(Self {
+                det: open(DET_MODEL_FILE)?,
+                rec: open(REC_MODEL_FILE)?,
+                dictionary,
+            })
+        }
+
+        /// 检测并识别整张图像中的文本行
+        pub fn run(&self, image: &CaptureView) -> Result<Vec<OcrLine>> {
+            let (input_w, input_h) = det_input_size(image.width(), image.height(), DET_LIMIT_SIDE);
+            let det_input = det_tensor(image, input_w, input_h);
+            let tensor = Tensor::from_array(([1usize, 3, input_h as usize, input_w as usize], det_input))?;
+            let outputs = self.det.run(ort::inputs![tensor]?)?;
+            let (_, prob) = outputs[0].try_extract_raw_tensor::<f32>()?;
+
+            let boxes = boxes_from_prob_map(prob, input_w, input_h, image.width(), image.height());
+
+            let mut lines = Vec::new();
+            for bbox in boxes {
+                let crop = RgbaImage::from_fn(bbox.width, bbox.height, |x, y| *image.get_pixel(bbox.x + x, bbox.y + y));
+                let (rec_input, rec_width) = rec_tensor(&crop);
+                let tensor = Tensor::from_array(([1usize, 3, REC_HEIGHT as usize, rec_width as usize], rec_input))?;
+                let outputs = self.rec.run(ort::inputs![tensor]?)?;
+                let (shape, probs) = outputs[0].try_extract_raw_tensor::<f32>()?;
+
+                if shape.len() != 3 {
+                    return Err(anyhow::anyhow!("识别模型输出维度异常: {:?}", shape));
+                }
+                let steps = shape[1] as usize;
+                let classes = shape[2] as usize;
+                let decoded = ctc_decode(probs, steps, classes, &self.dictionary);
+
+                // 每个时间步对应裁剪图中的宽度（识别输入按高度等比缩放后右侧补零）
+                let step_width = rec_width as f32 / steps as f32 * bbox.height as f32 / REC_HEIGHT as f32;
+                if let Some(line) = decoded_to_line(&decoded, bbox, step_width) {
+                    lines.push(line);
+                }
+            }
+
+            Ok(lines)
+        }
+    }
+}
+
+/// 解析识别字典，末尾追加空格字符（与 PaddleOCR `use_space_char` 一致）
+pub fn load_dictionary(content: &str) -> Vec<String> {
+    let mut dictionary: Vec<String> = content
+        .lines()
+        .map(|line| line.trim_end_matches('\r').to_string())
+        .filter(|line| !line.is_empty())
+        .collect();
+    dictionary.push(" ".to_string());
+    dictionary
+}
+
+/// 计算检测输入尺寸：最长边不超过 `limit`，宽高均为 32 的倍数
+pub fn det_input_size(width: u32, height: u32, limit: u32) -> (u32, u32) {
+    let longest = width.max(height).max(1);
+    let ratio = if longest > limit {
+        limit as f32 / longest as f32
+    } else {
+        1.0
+    };
+
+    let round32 = |v: u32| -> u32 {
+        let scaled = (v as f32 * ratio).round() as u32;
+        (((scaled + 16) / 32) * 32).max(32)
+    };
+
+    (round32(width), round32(height))
+}
+
+/// 生成检测模型输入（NCHW，BGR 通道，ImageNet 均值方差归一化）
+pub fn det_tensor(image: &CaptureView, width: u32, height: u32) -> Vec<f32> {
+    const MEAN: [f32; 3] = [0.485, 0.456, 0.406];
+    const STD: [f32; 3] = [0.229, 0.224, 0.225];
+
+    let resized = imageops::resize(image, width, height, FilterType::Triangle);
+    let plane = (width * height) as usize;
+    let mut data = vec![0.0f32; plane * 3];
+
+    for (i, pixel) in resized.pixels().enumerate() {
+        let bgr = [pixel[2], pixel[1], pixel[0]];
+        for c in 0..3 {
+            data[c * plane + i] = (bgr[c] as f32 / 255.0 - MEAN[c]) / STD[c];
+        }
+    }
+
+    data
+}
+
+/// 从检测概率图中提取文本框，并映射回原图坐标
+///
+/// 使用连通域近似 DB 后处理：二值化 → 4 邻域连通域 → 平均得分过滤 → 按面积/周长外扩。
+pub fn boxes_from_prob_map(
+    prob: &[f32],
+    map_width: u32,
+    map_height: u32,
+    image_width: u32,
+    image_height: u32,
+) -> Vec<BoundingBox> {
+    let (w, h) = (map_width as usize, map_height as usize);
+    if prob.len() < w * h {
+        return Vec::new();
+    }
+
+    let mut visited = vec![false; w * h];
+    let mut boxes = Vec::new();
+    let mut stack = Vec::new();
+
+    for start in 0..w * h {
+        if visited[start] || prob[start] < DET_THRESHOLD {
+            continue;
+        }
+
+        // 泛洪填充当前连通域
+        let (mut min_x, mut min_y, mut max_x, mut max_y) = (usize::MAX, usize::MAX, 0, 0);
+        let mut area = 0usize;
+        let mut score = 0.0f32;
+        visited[start] = true;
+        stack.push(start);
+
+        while let Some(idx) = stack.pop() {
+            let (x, y) = (idx % w, idx / w);
+            min_x = min_x.min(x);
+            min_y = min_y.min(y);
+            max_x = max_x.max(x);
+            max_y = max_y.max(y);
+            area += 1;
+            score += prob[idx];
+
+            let mut visit = |n: usize| {
+                if !visited[n] && prob[n] >= DET_THRESHOLD {
+                    visited[n] = true;
+                    stack.push(n);
+                }
+            };
+            if x > 0 {
+                visit(idx - 1);
+            }
+            if x + 1 < w {
+                visit(idx + 1);
+            }
+            if y > 0 {
+                visit(idx - w);
+            }
+            if y + 1 < h {
+                visit(idx + w);
+            }
+        }
+
+        if area < DET_MIN_AREA || score / (area as f32) < DET_BOX_THRESHOLD {
+            continue;
+        }
+
+        // DB 的 unclip：外扩距离 = 面积 * ratio / 周长
+        let box_w = (max_x - min_x + 1) as f32;
+        let box_h = (max_y - min_y + 1) as f32;
+        let distance = box_w * box_h * DET_UNCLIP_RATIO / (2.0 * (box_w + box_h));
+
+        let scale_x = image_width as f32 / map_width as f32;
+        let scale_y = image_height as f32 / map_height as f32;
+        let left = ((min_x as f32 - distance) * scale_x).max(0.0);
+        let top = ((min_y as f32 - distance) * scale_y).max(0.0);
+        let right = ((max_x as f32 + 1.0 + distance) * scale_x).min(image_width as f32);
+        let bottom = ((max_y as f32 + 1.0 + distance) * scale_y).min(image_height as f32);
+
+        if right - left < 2.0 || bottom - top < 2.0 {
+            continue;
+        }
+
+        boxes.push(BoundingBox {
+            x: left as u32,
+            y: top as u32,
+            width: (right - left) as u32,
+            height: (bottom - top) as u32,
+        });
+    }
+
+    sort_reading_order(&mut boxes);
+    boxes
+}
+
+/// 按阅读顺序排序：从上到下，同一行内从左到右
+///
+/// 先按纵坐标分行，与行首文本框的纵向偏移小于半个行高的文本框属于同一行，再在行内按横坐标排序。
+/// 不能直接用“是否同一行”做比较函数：同一行的判断不满足传递性，排序结果不确定甚至会 panic。
+fn sort_reading_order(boxes: &mut Vec<BoundingBox>) {
+    boxes.sort_by_key(|bbox| (bbox.y, bbox.x));
+
+    let mut lines: Vec<Vec<BoundingBox>> = Vec::new();
+    for bbox in boxes.drain(..) {
+        match lines.last_mut() {
+            Some(line) if bbox.y - line[0].y < line[0].height.min(bbox.height) / 2 => line.push(bbox),
+            _ => lines.push(vec![bbox]),
+        }
+    }
+
+    for mut line in lines {
+        line.sort_by_key(|bbox| bbox.x);
+        boxes.extend(line);
+    }
+}
+
+/// 生成识别模型输入：高度缩放到 48，宽度等比缩放后右侧补零，归一化到 [-1, 1]
+///
+/// 返回数据和输入宽度。
+pub fn rec_tensor(crop: &RgbaImage) -> (Vec<f32>, u32) {
+    let ratio = crop.width() as f32 / crop.height().max(1) as f32;
+    let resized_w = ((REC_HEIGHT as f32 * ratio).ceil() as u32).max(1);
+    let input_w = resized_w.max(REC_MIN_WIDTH);
+
+    let resized = imageops::resize(crop, resized_w, REC_HEIGHT, FilterType::Triangle);
+    let plane = (input_w * REC_HEIGHT) as usize;
+    let mut data = vec![0.0f32; plane * 3];
+
+    for (x, y, pixel) in resized.enumerate_pixels() {
+        let i = (y * input_w + x) as usize;
+        let bgr = [pixel[2], pixel[1], pixel[0]];
+        for c in 0..3 {
+            data[c * plane + i] = (bgr[c] as f32 / 255.0 - 0.5) / 0.5;
+        }
+    }
+
+    (data, input_w)
+}
+
+/// CTC 解码结果
+#[derive(Debug, Clone, PartialEq)]
+pub struct Decoded {
+    /// 每个字符及其所在时间步和概率
+    pub chars: Vec<(String, usize, f32)>,
+}
+
+impl Decoded {
+    pub fn text(&self) -> String {
+        self.chars.iter().map(|(c, _, _)| c.as_str()).collect()
+    }
+}
+
+/// CTC 贪心解码：索引 0 为 blank，连续重复的索引只保留一次
+pub fn ctc_decode(probs: &[f32], steps: usize, classes: usize, dictionary: &[String]) -> Decoded {
+    let mut chars = Vec::new();
+    let mut last = 0usize;
+
+    for t in 0..steps {
+        let row = &probs[t * classes..(t + 1) * classes];
+        let (index, &prob) = row
+            .iter()
+            .enumerate()
+            .max_by(|a, b| a.1.partial_cmp(b.1).unwrap_or(std::cmp::Ordering::Equal))
+            .unwrap_or((0, &0.0));
+
+        if index != 0 && index != last {
+            if let Some(ch) = dictionary.get(index - 1) {
+                chars.push((ch.clone(), t, prob));
+            }
+        }
+        last = index;
+    }
+
+    Decoded { chars }
+}
+
+/// 将解码结果转换为文本行，按空格拆分单词并根据时间步估算单词边界框
+pub fn decoded_to_line(decoded: &Decoded, bbox: BoundingBox, step_width: f32) -> Option<OcrLine> {
+    let text = decoded.text().trim().to_string();
+    if text.is_empty() {
+        return None;
+    }
+
+    let mut words = Vec::new();
+    let mut current: Vec<&(String, usize, f32)> = Vec::new();
+    let mut flush = |current: &mut Vec<&(String, usize, f32)>| {
+        if current.is_empty() {
+            return;
+        }
+        let first = current[0].1 as f32;
+        let last = current[current.len() - 1].1 as f32 + 1.0;
+        let x = bbox.x + (first * step_width) as u32;
+        let right = (bbox.x + (last * step_width).ceil() as u32).min(bbox.x + bbox.width);
+        words.push(OcrWord {
+            text: current.iter().map(|(c, _, _)| c.as_str()).collect(),
+            bbox: BoundingBox {
+                x,
+                y: bbox.y,
+                width: right.saturating_sub(x).max(1),
+                height: bbox.height,
+            },
+            confidence: current.iter().map(|(_, _, p)| p).sum::<f32>() / current.len() as f32,
+        });
+        current.clear();
+    };
+
+    for ch in &decoded.chars {
+        if ch.0.trim().is_empty() {
+            flush(&mut current);
+        } else {
+            current.push(ch);
+        }
+    }
+    flush(&mut current);
+
+    Some(OcrLine { text, bbox, words })
+}
+
+/// 将相邻的文本行合并为文本块：垂直间距小于行高且水平方向重叠
+pub fn group_lines_into_blocks(lines: Vec<OcrLine>) -> Vec<OcrBlock> {
+    let mut blocks: Vec<OcrBlock> = Vec::new();
+
+    for line in lines {
+        let joins_last = blocks.last().is_some_and(|block| {
+            let last = &block.lines[block.lines.len() - 1].bbox;
+            let gap = line.bbox.y as i64 - (last.y + last.height) as i64;
+            let overlaps = line.bbox.x < block.bbox.x + block.bbox.width
+                && block.bbox.x < line.bbox.x + line.bbox.width;
+            gap >= -(last.height as i64 / 2) && gap < last.height.max(line.bbox.height) as i64 && overlaps
+        });
+
+        if joins_last {
+            let block = blocks.last_mut().unwrap();
+            block.bbox = block.bbox.union(&line.bbox);
+            block.text.push('\n');
+            block.text.push_str(&line.text);
+            block.lines.push(line);
+        } else {
+            blocks.push(OcrBlock {
+                text: line.text.clone(),
+                bbox: line.bbox,
+                lines: vec![line],
+            });
+        }
+    }
+
+    blocks
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_det_input_size() {
+        assert_eq!(det_input_size(1920, 1080, 960), (960, 544));
+        assert_eq!(det_input_size(100, 20, 960), (96, 32));
+    }
+
+    #[test]
+    fn test_boxes_from_prob_map() {
+        // 8x4 概率图，左上 5x2 为文本区域
+        let mut prob = vec![0.0f32; 8 * 4];
+        for y in 0..2 {
+            for x in 0..5 {
+                prob[y * 8 + x] = 0.9;
+            }
+        }
+
+        let boxes = boxes_from_prob_map(&prob, 8, 4, 80, 40);
+        assert_eq!(boxes.len(), 1);
+        assert_eq!(boxes[0].x, 0);
+        assert_eq!(boxes[0].y, 0);
+        assert!(boxes[0].width > 50 && boxes[0].width <= 80);
+    }
+
+    #[test]
+    fn test_sort_reading_order() {
+        let bbox = |x: u32, y: u32| BoundingBox { x, y, width: 30, height: 20 };
+        // 相邻两个框的纵向偏移都小于半个行高，但第一个和第三个不是同一行
+        let mut boxes = vec![bbox(100, 0), bbox(0, 8), bbox(50, 16), bbox(70, 60), bbox(10, 62)];
+        sort_reading_order(&mut boxes);
+        let order: Vec<_> = boxes.iter().map(|b| (b.x, b.y)).collect();
+        assert_eq!(order, [(0, 8), (100, 0), (50, 16), (10, 62), (70, 60)]);
+
+        // 逐渐下移的文本框不会让排序出错
+        let mut staircase: Vec<_> = (0..100).map(|i| bbox((i * 37) % 500, i * 3)).collect();
+        sort_reading_order(&mut staircase);
+        assert_eq!(staircase.len(), 100);
+    }
+
+    #[test]
+    fn test_ctc_decode_and_word_boxes() {
+        let dictionary = load_dictionary("a\nb\n");
+        // 类别: 0 blank, 1 'a', 2 'b', 3 ' '
+        let steps = [1, 1, 0, 2, 3, 1, 0];
+        let mut probs = vec![0.0f32; steps.len() * 4];
+        for (t, &class) in steps.iter().enumerate() {
+            probs[t * 4 + class] = 0.8;
+        }
+
+        let decoded = ctc_decode(&probs, steps.len(), 4, &dictionary);
+        assert_eq!(decoded.text(), "ab a");
+        assert!(decoded.chars.iter().all(|(_, _, p)| (p - 0.8).abs() < 1e-6));
+
+        let bbox = BoundingBox { x: 100, y: 50, width: 70, height: 20 };
+        let line = decoded_to_line(&decoded, bbox, 10.0).unwrap();
+        assert_eq!(line.words.len(), 2);
+        assert_eq!(line.words[0].text, "ab");
+        assert_eq!(line.words[0].bbox.x, 100);
+        assert_eq!(line.words[1].bbox.x, 150);
+    }
+
+    #[test]
+    fn test_group_lines_into_blocks() {
+        let line = |text: &str, y: u32| OcrLine {
+            text: text.to_string(),
+            bbox: BoundingBox { x: 10, y, width: 100, height: 20 },
+            words: Vec::new(),
+        };
+
+        let blocks = group_lines_into_blocks(vec![line("one", 0), line("two", 25), line("three", 200)]);
+        assert_eq!(blocks.len(), 2);
+        assert_eq!(blocks[0].text, "one\ntwo");
+        assert_eq!(blocks[0].bbox.height, 45);
+    }
+}
@@ -0,0 +1,537 @@
+//! OCR 流水线：截图来源 → 裁剪 → 预处理 → 识别 → 后处理 → 输出
+//!
+//! 热键、托盘、前端命令、实时识别和命令行工具共用同一套流程，区别只在于图像来源、
+//! 触发方式和注册的输出（剪贴板、历史、前端事件等）。每个阶段都可以单独调用。
+//!
+//! 可以扩展的阶段：截图来源（[`ScreenSource`]）、内置预处理之后的 [`PreProcessor`]、
+//! 识别引擎（注册到 [`OcrRegistry`] 的后端）、[`PostProcessor`] 和 [`ResultSink`]。
+
+use anyhow::{Context, Result};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use crate::config::AppConfig;
+use crate::error::AppError;
+use crate::history::{self, HistoryRegion, HistoryStore, NewHistoryEntry};
+use crate::jobs::JobStage;
+use crate::ocr::{OcrRegistry, OcrResult};
+use crate::preprocess::{self, Preprocessed};
+use crate::screenshot::{self, CaptureRegion, ScreenCapture, ScreenRegion, ScreenSource};
+
+/// 要识别的图像从哪里来
+#[derive(Debug, Clone)]
+pub enum Source {
+    /// 主屏幕
+    PrimaryScreen,
+    /// 虚拟桌面中的区域，可以跨越多个显示器
+    Region(ScreenRegion),
+    /// 剪贴板中的图片
+    Clipboard,
+    /// 已有的图像（图片文件、标准输入等）
+    Image(ScreenCapture),
+}
+
+/// 触发识别的方式，输出阶段据此决定是否处理本次结果
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Trigger {
+    /// 前端调用命令，结果直接返回给调用方
+    Command,
+    /// 热键或托盘菜单，结果通过事件发送给前端
+    Hotkey,
+    /// 实时识别的周期性识别，不复制也不记录历史
+    Live,
+}
+
+/// 一次识别请求
+#[derive(Debug, Clone)]
+pub struct OcrRequest {
+    pub source: Source,
+    pub trigger: Trigger,
+    /// 本次识别使用的配置（可能已按前台窗口套用了方案）
+    pub config: AppConfig,
+    /// 截图前等待的时间（全屏识别的触发延时）
+    pub delay: Duration,
+    /// 在截图中进一步裁剪的区域，坐标相对截图左上角
+    pub crop: Option<CaptureRegion>,
+}
+
+impl OcrRequest {
+    pub fn new(source: Source, trigger: Trigger, config: AppConfig) -> Self {
+        Self {
+            source,
+            trigger,
+            config,
+            delay: Duration::ZERO,
+            crop: None,
+        }
+    }
+
+    pub fn with_delay(mut self, delay: Duration) -> Self {
+        self.delay = delay;
+        self
+    }
+
+    pub fn with_crop(mut self, crop: CaptureRegion) -> Self {
+        self.crop = Some(crop);
+        self
+    }
+}
+
+/// 一次识别的结果，交给各个输出阶段
+#[derive(Debug, Clone)]
+pub struct OcrOutput {
+    pub trigger: Trigger,
+    pub config: AppConfig,
+    /// 来源为虚拟桌面区域时的区域
+    pub region: Option<ScreenRegion>,
+    /// 识别的图像（裁剪后、预处理前）
+    pub capture: ScreenCapture,
+    /// 识别结果，边界框相对 `capture` 左上角
+    pub result: OcrResult,
+}
+
+/// 预处理阶段，在内置预处理之后、识别之前修改图像
+///
+/// 改变图像尺寸或方向时需要在 `transforms` 中记录，识别结果才能映射回原图坐标。
+pub trait PreProcessor: Send + Sync {
+    fn name(&self) -> &str;
+
+    fn process(&self, image: &mut Preprocessed, config: &AppConfig) -> Result<()>;
+}
+
+/// 后处理阶段，在结果交给输出之前修改它（例如纠正常见的识别错误）
+pub trait PostProcessor: Send + Sync {
+    fn name(&self) -> &str;
+
+    fn process(&self, result: &mut OcrResult, config: &AppConfig) -> Result<()>;
+}
+
+/// 输出阶段，失败时只打印警告，不影响识别结果
+pub trait ResultSink: Send + Sync {
+    /// 用于日志的名称，例如 "复制到剪贴板"
+    fn name(&self) -> &str;
+
+    fn deliver(&self, output: &OcrOutput) -> Result<()>;
+}
+
+/// OCR 流水线
+pub struct OcrPipeline {
+    screen_source: Arc<dyn ScreenSource>,
+    registry: OcrRegistry,
+    pre_processors: Vec<Arc<dyn PreProcessor>>,
+    post_processors: Vec<Arc<dyn PostProcessor>>,
+    /// 输出通常依赖运行时才有的对象（例如 Tauri 的 AppHandle），允许启动后再添加
+    sinks: Mutex<Vec<Arc<dyn ResultSink>>>,
+}
+
+impl OcrPipeline {
+    pub fn new(screen_source: Arc<dyn ScreenSource>, registry: OcrRegistry) -> Self {
+        Self {
+            screen_source,
+            registry,
+            pre_processors: Vec::new(),
+            post_processors: Vec::new(),
+            sinks: Mutex::new(Vec::new()),
+        }
+    }
+
+    pub fn registry(&self) -> &OcrRegistry {
+        &self.registry
+    }
+
+    pub fn screen_source(&self) -> &Arc<dyn ScreenSource> {
+        &self.screen_source
+    }
+
+    /// 添加预处理阶段，按添加顺序执行
+    pub fn add_pre_processor(&mut self, processor: Arc<dyn PreProcessor>) {
+        self.pre_processors.push(processor);
+    }
+
+    /// 添加后处理阶段，按添加顺序执行
+    pub fn add_post_processor(&mut self, processor: Arc<dyn PostProcessor>) {
+        self.post_processors.push(processor);
+    }
+
+    /// 添加输出阶段，按添加顺序执行
+    pub fn add_sink(&self, sink: Arc<dyn ResultSink>) {
+        self.sinks.lock().unwrap().push(sink);
+    }
+
+    /// 执行完整的流水线
+    pub async fn run(&self, request: OcrRequest) -> Result<OcrOutput, AppError> {
+        self.run_with_progress(request, |_| {}).await
+    }
+
+    /// 执行完整的流水线，进入截图、预处理和识别阶段时调用 `progress`
+    pub async fn run_with_progress(
+        &self,
+        request: OcrRequest,
+        progress: impl Fn(JobStage),
+    ) -> Result<OcrOutput, AppError> {
+        let OcrRequest { source, trigger, config, delay, crop } = request;
+
+        if !delay.is_zero() {
+            tokio::time::sleep(delay).await;
+        }
+
+        progress(JobStage::Capturing);
+
+        let region = match &source {
+            Source::Region(region) => Some(*region),
+            _ => None,
+        };
+        let mut capture = self.capture(source).await?;
+        if let Some(crop) = &crop {
+            capture = screenshot::crop_region(&capture, crop)?;
+        }
+
+        let result = self.recognize_with_progress(&capture, &config, &progress).await?;
+        if trigger != Trigger::Live {
+            println!("✅ OCR 完成，识别了 {} 个字符", result.text.len());
+        }
+
+        let output = OcrOutput {
+            trigger,
+            config,
+            region,
+            capture,
+            result,
+        };
+        self.deliver(&output);
+        Ok(output)
+    }
+
+    /// 截图阶段
+    pub async fn capture(&self, source: Source) -> Result<ScreenCapture, AppError> {
+        let capture = match source {
+            Source::PrimaryScreen => self.screen_source.capture_primary().context("截图失败"),
+            Source::Region(region) => self.screen_source.capture_region(&region).context("区域截图失败"),
+            Source::Clipboard => tokio::task::spawn_blocking(screenshot::clipboard_image)
+                .await
+                .map_err(anyhow::Error::from)
+                .and_then(|capture| capture)
+                .context("读取剪贴板失败"),
+            Source::Image(capture) => Ok(capture),
+        };
+        capture.map_err(AppError::Capture)
+    }
+
+    /// 预处理、识别和后处理阶段，结果的边界框已映射回 `capture` 的坐标
+    pub async fn recognize(&self, capture: &ScreenCapture, config: &AppConfig) -> Result<OcrResult, AppError> {
+        self.recognize_with_progress(capture, config, &|_| {}).await
+    }
+
+    async fn recognize_with_progress(
+        &self,
+        capture: &ScreenCapture,
+        config: &AppConfig,
+        progress: &impl Fn(JobStage),
+    ) -> Result<OcrResult, AppError> {
+        let engine = config.ocr_engine.as_str();
+        let backend = self.registry.get(engine).map_err(|e| AppError::EngineUnavailable {
+            engine: engine.to_string(),
+            reason: format!("{:#}", e),
+        })?;
+        let missing = backend.missing_languages(&config.ocr_language);
+        if !missing.is_empty() {
+            return Err(AppError::LanguageMissing {
+                engine: engine.to_string(),
+                missing,
+            });
+        }
+
+        progress(JobStage::Preprocessing);
+        let mut prepared = preprocess::apply(capture, &config.preprocess).map_err(AppError::Preprocess)?;
+        for processor in &self.pre_processors {
+            processor
+                .process(&mut prepared, config)
+                .with_context(|| format!("预处理 {} 失败", processor.name()))
+                .map_err(AppError::Preprocess)?;
+        }
+
+        progress(JobStage::Recognizing);
+        let timeout = config.ocr_timeout(config.ocr_engine);
+        let recognition = tokio::time::timeout(timeout, backend.recognize(&prepared.capture, &config.ocr_language));
+        let mut result = match recognition.await {
+            Err(_) => {
+                return Err(AppError::Timeout {
+                    timeout_ms: timeout.as_millis() as u64,
+                });
+            }
+            Ok(Ok(result)) => result,
+            // 引擎在当前平台或构建中不可用时，识别错误说明了原因
+            Ok(Err(e)) if !backend.capabilities().available => {
+                return Err(AppError::EngineUnavailable {
+                    engine: engine.to_string(),
+                    reason: format!("{:#}", e),
+                });
+            }
+            Ok(Err(e)) => return Err(AppError::Recognition(e)),
+        };
+        prepared.restore(&mut result);
+
+        for processor in &self.post_processors {
+            processor
+                .process(&mut result, config)
+                .with_context(|| format!("后处理 {} 失败", processor.name()))
+                .map_err(AppError::Recognition)?;
+        }
+        Ok(result)
+    }
+
+    /// 输出阶段
+    pub fn deliver(&self, output: &OcrOutput) {
+        let sinks = self.sinks.lock().unwrap().clone();
+        for sink in sinks {
+            if let Err(e) = sink.deliver(output) {
+                eprintln!("⚠️  {}失败: {:#}", sink.name(), e);
+            }
+        }
+    }
+}
+
+/// 把识别结果写入历史（实时识别和空结果除外）
+pub struct HistorySink {
+    store: Arc<HistoryStore>,
+}
+
+impl HistorySink {
+    pub fn new(store: Arc<HistoryStore>) -> Self {
+        Self { store }
+    }
+}
+
+impl ResultSink for HistorySink {
+    fn name(&self) -> &str {
+        "记录历史"
+    }
+
+    fn deliver(&self, output: &OcrOutput) -> Result<()> {
+        let config = &output.config;
+        if output.trigger == Trigger::Live || !config.history_enabled || output.result.text.trim().is_empty() {
+            return Ok(());
+        }
+
+        let thumbnail = if config.history_thumbnails {
+            history::make_thumbnail(&output.capture)
+                .map_err(|e| eprintln!("⚠️  生成缩略图失败: {}", e))
+                .ok()
+        } else {
+            None
+        };
+
+        let entry = NewHistoryEntry {
+            text: output.result.text.clone(),
+            confidence: output.result.confidence,
+            engine: config.ocr_engine.to_string(),
+            language: output.result.language.clone(),
+            region: output.region.map(|region| HistoryRegion {
+                x: region.x,
+                y: region.y,
+                width: region.width,
+                height: region.height,
+            }),
+            thumbnail,
+        };
+        self.store.record(entry, config.history_limit)?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::testing::{self, config};
+    use super::*;
+    use crate::ocr::BoundingBox;
+    use crate::preprocess::Transform;
+
+    struct Uppercase;
+
+    impl PostProcessor for Uppercase {
+        fn name(&self) -> &str {
+            "uppercase"
+        }
+
+        fn process(&self, result: &mut OcrResult, _config: &AppConfig) -> Result<()> {
+            result.text = result.text.to_uppercase();
+            Ok(())
+        }
+    }
+
+    /// 把图像放大一倍
+    struct Double;
+
+    impl PreProcessor for Double {
+        fn name(&self) -> &str {
+            "double"
+        }
+
+        fn process(&self, image: &mut Preprocessed, _config: &AppConfig) -> Result<()> {
+            let capture = &image.capture;
+            let (width, height) = (capture.width * 2, capture.height * 2);
+            image.capture = ScreenCapture::new(width, height, vec![255; (width * height * 4) as usize], capture.x, capture.y);
+            image.transforms.push(Transform::Scale(2));
+            Ok(())
+        }
+    }
+
+    #[derive(Default)]
+    struct CollectSink {
+        outputs: Mutex<Vec<(Trigger, String)>>,
+    }
+
+    impl ResultSink for CollectSink {
+        fn name(&self) -> &str {
+            "collect"
+        }
+
+        fn deliver(&self, output: &OcrOutput) -> Result<()> {
+            self.outputs.lock().unwrap().push((output.trigger, output.result.text.clone()));
+            Ok(())
+        }
+    }
+
+    fn pipeline() -> OcrPipeline {
+        testing::pipeline(400, 300, Duration::ZERO)
+    }
+
+    #[tokio::test]
+    async fn test_sources_and_crop() {
+        let pipeline = pipeline();
+
+        let output = pipeline.run(OcrRequest::new(Source::PrimaryScreen, Trigger::Command, config())).await.unwrap();
+        assert_eq!(output.result.text, "400x300");
+
+        let region = ScreenRegion { x: 10, y: 20, width: 100, height: 50 };
+        let output = pipeline.run(OcrRequest::new(Source::Region(region), Trigger::Command, config())).await.unwrap();
+        assert_eq!(output.result.text, "100x50");
+        assert_eq!((output.capture.x, output.capture.y), (10, 20));
+
+        let crop = CaptureRegion { x: 5, y: 5, width: 30, height: 20 };
+        let request = OcrRequest::new(Source::Region(region), Trigger::Command, config()).with_crop(crop);
+        let output = pipeline.run(request).await.unwrap();
+        assert_eq!(output.result.text, "30x20");
+        assert_eq!((output.capture.x, output.capture.y), (15, 25));
+
+        let crop = CaptureRegion { x: 90, y: 0, width: 20, height: 10 };
+        let request = OcrRequest::new(Source::Region(region), Trigger::Command, config()).with_crop(crop);
+        let error = pipeline.run(request).await.unwrap_err();
+        assert_eq!(error.code(), "crop_out_of_bounds");
+    }
+
+    #[tokio::test]
+    async fn test_recognize_unscales_preprocessed_result() {
+        let pipeline = pipeline();
+        let capture = pipeline.capture(Source::Region(ScreenRegion { x: 0, y: 0, width: 60, height: 40 })).await.unwrap();
+
+        // 默认预处理会把矮图像放大，边界框需要映射回原图
+        let result = pipeline.recognize(&capture, &AppConfig::default()).await.unwrap();
+        assert_ne!(result.text, "60x40");
+        assert_eq!(result.blocks[0].bbox, BoundingBox { x: 0, y: 0, width: 60, height: 40 });
+    }
+
+    #[tokio::test]
+    async fn test_pre_processors_record_transforms() {
+        let mut pipeline = pipeline();
+        pipeline.add_pre_processor(Arc::new(Double));
+
+        let capture = ScreenCapture::new(60, 40, vec![255; 60 * 40 * 4], 0, 0);
+        let result = pipeline.recognize(&capture, &config()).await.unwrap();
+        assert_eq!(result.text, "120x80");
+        assert_eq!(result.blocks[0].bbox, BoundingBox { x: 0, y: 0, width: 60, height: 40 });
+    }
+
+    #[tokio::test]
+    async fn test_post_processors_and_sinks() {
+        let mut pipeline = pipeline();
+        pipeline.add_post_processor(Arc::new(Uppercase));
+        let sink = Arc::new(CollectSink::default());
+        pipeline.add_sink(sink.clone());
+
+        let capture = ScreenCapture::new(2, 1, vec![0; 8], 0, 0);
+        pipeline.run(OcrRequest::new(Source::Image(capture), Trigger::Hotkey, config())).await.unwrap();
+        assert_eq!(*sink.outputs.lock().unwrap(), [(Trigger::Hotkey, "2X1".to_string())]);
+
+        // 未知引擎在识别阶段失败，不会交给输出
+        let config = AppConfig { ocr_engine: crate::config::OcrEngine::PaddleOcr, ..config() };
+        let error = pipeline.run(OcrRequest::new(Source::PrimaryScreen, Trigger::Hotkey, config)).await.unwrap_err();
+        assert_eq!(error.code(), "engine_unavailable");
+        assert_eq!(sink.outputs.lock().unwrap().len(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_history_sink_skips_live() {
+        let store = Arc::new(HistoryStore::open_in_memory().unwrap());
+        let pipeline = pipeline();
+        pipeline.add_sink(Arc::new(HistorySink::new(store.clone())));
+
+        let region = ScreenRegion { x: -50, y: 0, width: 80, height: 60 };
+        for trigger in [Trigger::Live, Trigger::Hotkey] {
+            pipeline.run(OcrRequest::new(Source::Region(region), trigger, config())).await.unwrap();
+        }
+
+        let entries = store.list(10, 0).unwrap();
+        assert_eq!(entries.len(), 1);
+        assert_eq!(entries[0].text, "80x60");
+        assert_eq!(entries[0].region.as_ref().unwrap().x, -50);
+    }
+}
+
+/// 测试用的识别引擎和流水线，流水线和任务管理的测试共用
+#[cfg(test)]
+pub(crate) mod testing {
+    use super::*;
+    use async_trait::async_trait;
+    use crate::ocr::{BackendCapabilities, BoundingBox, OcrBackend, OcrBlock, OcrLine, OcrWord};
+    use crate::screenshot::ImageScreenSource;
+
+    /// 等待 `delay` 后把图像尺寸作为识别文本，边界框覆盖整张图像
+    pub struct FakeBackend {
+        pub delay: Duration,
+    }
+
+    #[async_trait]
+    impl OcrBackend for FakeBackend {
+        fn name(&self) -> &str {
+            "Tesseract"
+        }
+
+        fn supported_languages(&self) -> Vec<String> {
+            vec!["eng".to_string()]
+        }
+
+        fn capabilities(&self) -> BackendCapabilities {
+            BackendCapabilities {
+                available: true,
+                offline: true,
+                multi_language: false,
+                word_boxes: true,
+            }
+        }
+
+        async fn recognize(&self, capture: &ScreenCapture, language: &str) -> Result<OcrResult> {
+            if !self.delay.is_zero() {
+                tokio::time::sleep(self.delay).await;
+            }
+            let text = format!("{}x{}", capture.width, capture.height);
+            let bbox = BoundingBox { x: 0, y: 0, width: capture.width, height: capture.height };
+            let words = vec![OcrWord { text: text.clone(), bbox, confidence: 0.9 }];
+            let lines = vec![OcrLine { text: text.clone(), bbox, words }];
+            Ok(OcrResult::from_blocks(vec![OcrBlock { text, bbox, lines }], language))
+        }
+    }
+
+    /// 截图为纯白 `width`x`height` 屏幕、识别引擎为 [`FakeBackend`] 的流水线
+    pub fn pipeline(width: u32, height: u32, delay: Duration) -> OcrPipeline {
+        let mut registry = OcrRegistry::new();
+        registry.register(Arc::new(FakeBackend { delay }));
+        OcrPipeline::new(Arc::new(ImageScreenSource::solid(width, height, [255, 255, 255, 255])), registry)
+    }
+
+    /// 不做预处理，识别文本即为图像尺寸
+    pub fn config() -> AppConfig {
+        let mut config = AppConfig::default();
+        config.preprocess.steps.clear();
+        config
+    }
+}
@@ -0,0 +1,514 @@
+use anyhow::Result;
+use image::{imageops, imageops::FilterType, ImageBuffer, Rgba, RgbaImage};
+use serde::{Deserialize, Serialize};
+use std::ops::Deref;
+use crate::ocr::OcrResult;
+use crate::screenshot::{CaptureView, ScreenCapture};
+
+/// 预处理步骤
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum PreprocessStep {
+    /// 转为灰度
+    Grayscale,
+    /// 自适应二值化（局部均值阈值）
+    Binarize,
+    /// 检测深色背景并反色（浅色文字 → 深色文字）
+    InvertDark,
+    /// 小区域按整数倍放大
+    Upscale,
+    /// 3x3 中值滤波去噪
+    Denoise,
+    /// 纠正轻微倾斜
+    Deskew,
+}
+
+/// 预处理配置
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct PreprocessConfig {
+    /// 按顺序执行的步骤
+    pub steps: Vec<PreprocessStep>,
+    /// 图像高度低于该值时放大
+    pub upscale_min_height: u32,
+    /// 最大放大倍数
+    pub max_upscale: u32,
+    /// 自适应二值化的窗口大小（像素）
+    pub binarize_window: u32,
+    /// 自适应二值化的阈值偏移
+    pub binarize_offset: u8,
+    /// 纠偏的最大角度（度）
+    pub max_skew_degrees: f32,
+}
+
+impl Default for PreprocessConfig {
+    fn default() -> Self {
+        Self {
+            steps: vec![PreprocessStep::InvertDark, PreprocessStep::Upscale],
+            upscale_min_height: 200,
+            max_upscale: 3,
+            binarize_window: 25,
+            binarize_offset: 10,
+            max_skew_degrees: 5.0,
+        }
+    }
+}
+
+/// 预处理对图像做的几何变换
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Transform {
+    /// 按整数倍放大
+    Scale(u32),
+    /// 绕 `center` 旋转（度，顺时针为正），`center` 是旋转时图像的中心
+    Rotate { degrees: f32, center: (f32, f32) },
+}
+
+/// 预处理结果
+#[derive(Debug, Clone)]
+pub struct Preprocessed {
+    pub capture: ScreenCapture,
+    /// 按执行顺序记录的几何变换，识别结果的坐标需要逆向还原
+    pub transforms: Vec<Transform>,
+}
+
+impl Preprocessed {
+    /// 总的放大倍数
+    pub fn scale(&self) -> u32 {
+        self.transforms
+            .iter()
+            .map(|transform| match transform {
+                Transform::Scale(factor) => *factor,
+                Transform::Rotate { .. } => 1,
+            })
+            .product()
+    }
+
+    /// 把识别结果中的边界框从预处理后的图像映射回原图坐标
+    pub fn restore(&self, result: &mut OcrResult) {
+        for transform in self.transforms.iter().rev() {
+            match *transform {
+                Transform::Scale(factor) => result.unscale(factor),
+                Transform::Rotate { degrees, center } => result.map_boxes(|bbox| bbox.rotated(-degrees, center)),
+            }
+        }
+    }
+}
+
+/// 按配置依次执行预处理步骤
+pub fn apply(capture: &ScreenCapture, config: &PreprocessConfig) -> Result<Preprocessed> {
+    let mut transforms = Vec::new();
+
+    if config.steps.is_empty() {
+        return Ok(Preprocessed {
+            capture: capture.clone(),
+            transforms,
+        });
+    }
+
+    // 像素在第一个修改它的步骤才复制；没有步骤修改像素时（例如浅色背景且足够高的截图）直接共享原截图
+    let source = capture.view()?;
+    let mut img: Option<RgbaImage> = None;
+
+    for step in &config.steps {
+        match step {
+            PreprocessStep::Grayscale => grayscale(owned(&mut img, &source)),
+            PreprocessStep::Binarize => binarize(owned(&mut img, &source), config.binarize_window, config.binarize_offset),
+            PreprocessStep::InvertDark => {
+                if is_dark(&current(&img, &source)) {
+                    invert(owned(&mut img, &source));
+                }
+            }
+            PreprocessStep::Upscale => {
+                let upscaled = {
+                    let current = current(&img, &source);
+                    let factor = upscale_factor(current.height(), config.upscale_min_height, config.max_upscale);
+                    upscale(&current, factor).map(|upscaled| (upscaled, factor))
+                };
+                if let Some((upscaled, factor)) = upscaled {
+                    img = Some(upscaled);
+                    transforms.push(Transform::Scale(factor));
+                }
+            }
+            PreprocessStep::Denoise => {
+                let denoised = denoise(&current(&img, &source));
+                img = Some(denoised);
+            }
+            PreprocessStep::Deskew => {
+                let angle = estimate_skew(&current(&img, &source), config.max_skew_degrees);
+                if angle.abs() > f32::EPSILON {
+                    let rotated = rotate(&current(&img, &source), -angle);
+                    transforms.push(Transform::Rotate {
+                        degrees: -angle,
+                        center: (rotated.width() as f32 / 2.0, rotated.height() as f32 / 2.0),
+                    });
+                    img = Some(rotated);
+                }
+            }
+        }
+    }
+
+    let capture = match img {
+        Some(img) => ScreenCapture::from_image(img, capture.x, capture.y),
+        None => capture.clone(),
+    };
+    Ok(Preprocessed { capture, transforms })
+}
+
+/// 以借用的形式读取当前图像：已经修改过时读取修改后的图像，否则读取截图
+fn current<'a>(img: &'a Option<RgbaImage>, source: &CaptureView<'a>) -> CaptureView<'a> {
+    match img {
+        Some(img) => ImageBuffer::from_raw(img.width(), img.height(), img.as_raw().as_slice())
+            .expect("图像数据长度与尺寸一致"),
+        None => source.clone(),
+    }
+}
+
+/// 取得可以修改的图像，第一次修改时复制截图的像素
+fn owned<'a>(img: &'a mut Option<RgbaImage>, source: &CaptureView) -> &'a mut RgbaImage {
+    img.get_or_insert_with(|| {
+        ImageBuffer::from_raw(source.width(), source.height(), source.as_raw().to_vec())
+            .expect("图像数据长度与尺寸一致")
+    })
+}
+
+/// 像素亮度（ITU-R BT.601）
+fn luma(pixel: &Rgba<u8>) -> u8 {
+    ((pixel[0] as u32 * 299 + pixel[1] as u32 * 587 + pixel[2] as u32 * 114) / 1000) as u8
+}
+
+/// 转为灰度，保留 RGBA 布局
+pub fn grayscale(img: &mut RgbaImage) {
+    for pixel in img.pixels_mut() {
+        let l = luma(pixel);
+        *pixel = Rgba([l, l, l, pixel[3]]);
+    }
+}
+
+/// 自适应二值化：像素亮度低于局部均值减去偏移时为黑色，否则为白色
+pub fn binarize(img: &mut RgbaImage, window: u32, offset: u8) {
+    let (w, h) = (img.width() as usize, img.height() as usize);
+    if w == 0 || h == 0 {
+        return;
+    }
+
+    // 积分图，尺寸 (w+1) x (h+1)
+    let mut integral = vec![0u64; (w + 1) * (h + 1)];
+    for y in 0..h {
+        let mut row_sum = 0u64;
+        for x in 0..w {
+            row_sum += luma(img.get_pixel(x as u32, y as u32)) as u64;
+            integral[(y + 1) * (w + 1) + x + 1] = integral[y * (w + 1) + x + 1] + row_sum;
+        }
+    }
+
+    let half = (window.max(3) / 2) as usize;
+    for y in 0..h {
+        for x in 0..w {
+            let (x0, y0) = (x.saturating_sub(half), y.saturating_sub(half));
+            let (x1, y1) = ((x + half + 1).min(w), (y + half + 1).min(h));
+            let area = ((x1 - x0) * (y1 - y0)) as u64;
+            let sum = integral[y1 * (w + 1) + x1] + integral[y0 * (w + 1) + x0]
+                - integral[y0 * (w + 1) + x1]
+                - integral[y1 * (w + 1) + x0];
+            let mean = (sum / area) as i32;
+
+            let pixel = img.get_pixel_mut(x as u32, y as u32);
+            let value = if (luma(pixel) as i32) < mean - offset as i32 { 0 } else { 255 };
+            *pixel = Rgba([value, value, value, pixel[3]]);
+        }
+    }
+}
+
+/// 是否为深色背景（超过一半像素偏暗）
+pub fn is_dark<C: Deref<Target = [u8]>>(img: &ImageBuffer<Rgba<u8>, C>) -> bool {
+    let total = img.width() as u64 * img.height() as u64;
+    let dark = img.pixels().filter(|p| luma(p) < 128).count() as u64;
+    total > 0 && dark * 2 > total
+}
+
+/// 反色，保留透明度
+pub fn invert(img: &mut RgbaImage) {
+    for pixel in img.pixels_mut() {
+        *pixel = Rgba([255 - pixel[0], 255 - pixel[1], 255 - pixel[2], pixel[3]]);
+    }
+}
+
+/// 深色背景时反色，返回是否执行了反色
+pub fn invert_if_dark(img: &mut RgbaImage) -> bool {
+    if !is_dark(img) {
+        return false;
+    }
+    invert(img);
+    true
+}
+
+/// 计算整数放大倍数，使高度不低于 `min_height`
+pub fn upscale_factor(height: u32, min_height: u32, max_factor: u32) -> u32 {
+    if height == 0 || height >= min_height {
+        return 1;
+    }
+    min_height.div_ceil(height).clamp(1, max_factor.max(1))
+}
+
+/// 按整数倍放大，`factor` 不大于 1 时返回 None，调用方继续使用原图而不复制
+pub fn upscale<C: Deref<Target = [u8]>>(img: &ImageBuffer<Rgba<u8>, C>, factor: u32) -> Option<RgbaImage> {
+    (factor > 1).then(|| imageops::resize(img, img.width() * factor, img.height() * factor, FilterType::CatmullRom))
+}
+
+/// 3x3 中值滤波
+pub fn denoise<C: Deref<Target = [u8]>>(img: &ImageBuffer<Rgba<u8>, C>) -> RgbaImage {
+    let (w, h) = img.dimensions();
+    let mut out = RgbaImage::new(w, h);
+
+    for y in 0..h {
+        for x in 0..w {
+            let mut window: [[u8; 9]; 3] = [[0; 9]; 3];
+            let mut n = 0;
+            for dy in -1i32..=1 {
+                for dx in -1i32..=1 {
+                    let sx = (x as i32 + dx).clamp(0, w as i32 - 1) as u32;
+                    let sy = (y as i32 + dy).clamp(0, h as i32 - 1) as u32;
+                    let p = img.get_pixel(sx, sy);
+                    for c in 0..3 {
+                        window[c][n] = p[c];
+                    }
+                    n += 1;
+                }
+            }
+
+            let alpha = img.get_pixel(x, y)[3];
+            let mut median = [0u8; 3];
+            for c in 0..3 {
+                window[c].sort_unstable();
+                median[c] = window[c][4];
+            }
+            out.put_pixel(x, y, Rgba([median[0], median[1], median[2], alpha]));
+        }
+    }
+
+    out
+}
+
+/// 通过投影轮廓估计倾斜角度（度，顺时针为正）
+///
+/// 对每个候选角度统计深色像素在旋转后各行的分布，文本行对齐时行投影的方差最大。
+pub fn estimate_skew<C: Deref<Target = [u8]>>(img: &ImageBuffer<Rgba<u8>, C>, max_degrees: f32) -> f32 {
+    let (w, h) = img.dimensions();
+    let dark: Vec<(f32, f32)> = img
+        .enumerate_pixels()
+        .filter(|(_, _, p)| luma(p) < 128)
+        .map(|(x, y, _)| (x as f32 - w as f32 / 2.0, y as f32 - h as f32 / 2.0))
+        .collect();
+
+    if dark.is_empty() || max_degrees <= 0.0 {
+        return 0.0;
+    }
+
+    let rows = (w + h) as usize * 2;
+    let mut best = (0.0f32, f64::MIN);
+    let steps = (max_degrees * 4.0).round() as i32;
+
+    for i in -steps..=steps {
+        let angle = i as f32 * 0.25;
+        let (sin, cos) = angle.to_radians().sin_cos();
+        let mut profile = vec![0u32; rows];
+        for &(x, y) in &dark {
+            let ry = -x * sin + y * cos + rows as f32 / 2.0;
+            if ry >= 0.0 && (ry as usize) < rows {
+                profile[ry as usize] += 1;
+            }
+        }
+
+        let mean = dark.len() as f64 / rows as f64;
+        let variance: f64 = profile.iter().map(|&c| (c as f64 - mean).powi(2)).sum();
+        // 方差相同时优先选择更小的角度
+        if variance > best.1 + 1e-9 || ((variance - best.1).abs() <= 1e-9 && angle.abs() < best.0.abs()) {
+            best = (angle, variance);
+        }
+    }
+
+    best.0
+}
+
+/// 绕中心旋转（度，顺时针为正），空白区域以角落像素的颜色填充
+pub fn rotate<C: Deref<Target = [u8]>>(img: &ImageBuffer<Rgba<u8>, C>, degrees: f32) -> RgbaImage {
+    let (w, h) = img.dimensions();
+    let background = *img.get_pixel(0, 0);
+    let (sin, cos) = degrees.to_radians().sin_cos();
+    let (cx, cy) = (w as f32 / 2.0, h as f32 / 2.0);
+
+    ImageBuffer::from_fn(w, h, |x, y| {
+        // 反向映射：输出像素来自源图中旋转 -degrees 的位置
+        let dx = x as f32 + 0.5 - cx;
+        let dy = y as f32 + 0.5 - cy;
+        let sx = dx * cos + dy * sin + cx;
+        let sy = -dx * sin + dy * cos + cy;
+
+        if sx < 0.0 || sy < 0.0 || sx >= w as f32 || sy >= h as f32 {
+            background
+        } else {
+            *img.get_pixel(sx as u32, sy as u32)
+        }
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ocr::{BoundingBox, OcrBlock};
+    use std::sync::Arc;
+
+    fn solid(width: u32, height: u32, value: u8) -> RgbaImage {
+        ImageBuffer::from_pixel(width, height, Rgba([value, value, value, 255]))
+    }
+
+    #[test]
+    fn test_grayscale() {
+        let mut img = ImageBuffer::from_pixel(2, 2, Rgba([255, 0, 0, 255]));
+        grayscale(&mut img);
+        let p = img.get_pixel(0, 0);
+        assert_eq!(p[0], p[1]);
+        assert_eq!(p[1], p[2]);
+        assert_eq!(p[0], 76);
+    }
+
+    #[test]
+    fn test_binarize_separates_text_from_background() {
+        // 浅灰背景上的一条深灰横线
+        let mut img = solid(40, 40, 200);
+        for x in 5..35 {
+            img.put_pixel(x, 20, Rgba([90, 90, 90, 255]));
+        }
+
+        binarize(&mut img, 15, 10);
+        assert_eq!(img.get_pixel(20, 20)[0], 0);
+        assert_eq!(img.get_pixel(20, 5)[0], 255);
+    }
+
+    #[test]
+    fn test_invert_dark_background() {
+        let mut dark = solid(10, 10, 30);
+        assert!(invert_if_dark(&mut dark));
+        assert_eq!(dark.get_pixel(0, 0)[0], 225);
+
+        let mut light = solid(10, 10, 230);
+        assert!(!invert_if_dark(&mut light));
+        assert_eq!(light.get_pixel(0, 0)[0], 230);
+    }
+
+    #[test]
+    fn test_upscale_small_region() {
+        assert_eq!(upscale_factor(40, 100, 3), 3);
+        assert_eq!(upscale_factor(60, 100, 3), 2);
+        assert_eq!(upscale_factor(300, 100, 3), 1);
+
+        assert!(upscale(&solid(10, 5, 0), 1).is_none());
+        let img = upscale(&solid(10, 5, 0), 2).unwrap();
+        assert_eq!(img.dimensions(), (20, 10));
+    }
+
+    #[test]
+    fn test_denoise_removes_salt() {
+        let mut img = solid(5, 5, 255);
+        img.put_pixel(2, 2, Rgba([0, 0, 0, 255]));
+        let out = denoise(&img);
+        assert_eq!(out.get_pixel(2, 2)[0], 255);
+    }
+
+    #[test]
+    fn test_deskew_detects_rotation() {
+        // 白底上的三条水平黑线，旋转 3 度后应检测到约 3 度倾斜
+        let mut img = solid(200, 120, 255);
+        for y in [30, 60, 90] {
+            for x in 20..180 {
+                img.put_pixel(x, y, Rgba([0, 0, 0, 255]));
+                img.put_pixel(x, y + 1, Rgba([0, 0, 0, 255]));
+            }
+        }
+        assert_eq!(estimate_skew(&img, 5.0), 0.0);
+
+        let skewed = rotate(&img, 3.0);
+        let angle = estimate_skew(&skewed, 5.0);
+        assert!((angle - 3.0).abs() <= 0.5, "angle = {}", angle);
+        assert!(estimate_skew(&rotate(&skewed, -angle), 5.0).abs() <= 0.5);
+    }
+
+    #[test]
+    fn test_apply_tracks_scale() {
+        let capture = ScreenCapture::from_image(solid(50, 20, 20), 100, 200);
+        let config = PreprocessConfig::default();
+
+        let result = apply(&capture, &config).unwrap();
+        assert_eq!(result.scale(), 3);
+        assert_eq!(result.capture.width, 150);
+        assert_eq!(result.capture.x, 100);
+        // 深色背景已反色
+        assert!(result.capture.data[0] > 200);
+    }
+
+    #[test]
+    fn test_apply_shares_unchanged_capture() {
+        // 浅色背景且足够高：既不反色也不放大，不复制像素
+        let capture = ScreenCapture::from_image(solid(50, 300, 230), 0, 0);
+        let result = apply(&capture, &PreprocessConfig::default()).unwrap();
+        assert!(result.transforms.is_empty());
+        assert!(Arc::ptr_eq(&result.capture.data, &capture.data));
+    }
+
+    /// 深色像素的外接矩形
+    fn dark_bounds(img: &RgbaImage) -> BoundingBox {
+        let dark: Vec<(u32, u32)> = img
+            .enumerate_pixels()
+            .filter(|(_, _, p)| luma(p) < 128)
+            .map(|(x, y, _)| (x, y))
+            .collect();
+        let left = dark.iter().map(|p| p.0).min().unwrap();
+        let top = dark.iter().map(|p| p.1).min().unwrap();
+        let right = dark.iter().map(|p| p.0).max().unwrap() + 1;
+        let bottom = dark.iter().map(|p| p.1).max().unwrap() + 1;
+        BoundingBox { x: left, y: top, width: right - left, height: bottom - top }
+    }
+
+    #[test]
+    fn test_restore_deskewed_boxes() {
+        // 倾斜 3 度的截图，右下角有一个“单词”，离旋转中心越远，不还原旋转时偏差越大
+        let mut img = solid(600, 300, 255);
+        for y in [60, 120] {
+            for x in 60..540 {
+                img.put_pixel(x, y, Rgba([0, 0, 0, 255]));
+            }
+        }
+        for y in 230..250 {
+            for x in 480..580 {
+                img.put_pixel(x, y, Rgba([0, 0, 0, 255]));
+            }
+        }
+        let skewed = rotate(&img, 3.0);
+        let word = dark_bounds(&imageops::crop_imm(&skewed, 440, 200, 160, 100).to_image());
+        let expected = BoundingBox { x: word.x + 440, y: word.y + 200, ..word };
+
+        let config = PreprocessConfig {
+            steps: vec![PreprocessStep::Deskew, PreprocessStep::Upscale],
+            upscale_min_height: 600,
+            ..PreprocessConfig::default()
+        };
+        let prepared = apply(&ScreenCapture::from_image(skewed, 0, 0), &config).unwrap();
+        assert!(matches!(prepared.transforms[0], Transform::Rotate { .. }));
+        assert_eq!(prepared.transforms[1], Transform::Scale(2));
+
+        // 模拟识别引擎在纠偏、放大后的图像上找到这个单词
+        let output = RgbaImage::from_raw(prepared.capture.width, prepared.capture.height, prepared.capture.data.to_vec()).unwrap();
+        let found = dark_bounds(&imageops::crop_imm(&output, 880, 400, 320, 200).to_image());
+        let bbox = BoundingBox { x: found.x + 880, y: found.y + 400, ..found };
+        let mut result = OcrResult::from_blocks(
+            vec![OcrBlock { text: "word".to_string(), bbox, lines: Vec::new() }],
+            "eng",
+        );
+
+        prepared.restore(&mut result);
+        let restored = result.blocks[0].bbox;
+        assert!(restored.x.abs_diff(expected.x) <= 3, "{:?} vs {:?}", restored, expected);
+        assert!(restored.y.abs_diff(expected.y) <= 3, "{:?} vs {:?}", restored, expected);
+        assert!(restored.width.abs_diff(expected.width) <= 6, "{:?} vs {:?}", restored, expected);
+        assert!(restored.height.abs_diff(expected.height) <= 6, "{:?} vs {:?}", restored, expected);
+    }
+}
@@ -1,7 +1,122 @@
 use anyhow::Result;
-use image::{ImageBuffer, Rgba};
+use base64::Engine;
+use image::codecs::png::{CompressionType, FilterType, PngEncoder};
+use image::{ColorType, ImageBuffer, ImageEncoder, Rgba, RgbaImage};
 use screenshots::Screen;
-use std::io::Cursor;
+use std::collections::VecDeque;
+use std::path::Path;
+use std::sync::{Arc, Mutex};
+use thiserror::Error;
+
+/// 截图来源
+///
+/// 默认使用 [`SystemScreenSource`] 截取真实屏幕；测试和无显示器环境可以使用
+/// [`ImageScreenSource`] 提供固定图像。
+pub trait ScreenSource: Send + Sync {
+    /// 截取所有屏幕
+    fn capture_all(&self) -> Result<Vec<ScreenCapture>>;
+    
+    /// 截取主屏幕
+    fn capture_primary(&self) -> Result<ScreenCapture> {
+        self.capture_all()?
+            .into_iter()
+            .next()
+            .ok_or_else(|| anyhow::anyhow!("未找到可用屏幕"))
+    }
+    
+    /// 截取虚拟桌面中的指定区域，区域可以跨越多个显示器
+    fn capture_region(&self, region: &ScreenRegion) -> Result<ScreenCapture> {
+        stitch_region(&self.capture_all()?, region)
+    }
+}
+
+/// 通过 `screenshots` crate 截取真实屏幕
+pub struct SystemScreenSource;
+
+impl ScreenSource for SystemScreenSource {
+    fn capture_all(&self) -> Result<Vec<ScreenCapture>> {
+        capture_all_screens()
+    }
+    
+    fn capture_primary(&self) -> Result<ScreenCapture> {
+        capture_primary_screen()
+    }
+}
+
+/// 由固定图像充当屏幕的截图来源，用于测试和无显示器环境
+pub struct ImageScreenSource {
+    screens: Vec<ScreenCapture>,
+}
+
+impl ImageScreenSource {
+    /// 使用给定的截图作为各个屏幕，第一个为主屏幕
+    pub fn new(screens: Vec<ScreenCapture>) -> Self {
+        Self { screens }
+    }
+    
+    /// 从图片文件加载单个屏幕
+    pub fn from_file(path: impl AsRef<Path>) -> Result<Self> {
+        let path = path.as_ref();
+        let img = image::open(path)
+            .map_err(|e| anyhow::anyhow!("无法打开图片 {:?}: {}", path, e))?
+            .to_rgba8();
+        
+        Ok(Self::new(vec![ScreenCapture::from_image(img, 0, 0)]))
+    }
+    
+    /// 纯色的单个屏幕
+    pub fn solid(width: u32, height: u32, color: [u8; 4]) -> Self {
+        let data = color
+            .iter()
+            .copied()
+            .cycle()
+            .take(width as usize * height as usize * 4)
+            .collect();
+        
+        Self::new(vec![ScreenCapture::new(width, height, data, 0, 0)])
+    }
+}
+
+impl ScreenSource for ImageScreenSource {
+    fn capture_all(&self) -> Result<Vec<ScreenCapture>> {
+        Ok(self.screens.clone())
+    }
+}
+
+/// 截图来源的环境变量，用于无显示器运行
+///
+/// - `file:<路径>`: 使用图片文件作为屏幕
+/// - `synthetic:<宽>x<高>`: 使用白色纯色屏幕
+pub const SCREEN_SOURCE_ENV: &str = "SCREENOCR_SCREEN_SOURCE";
+
+/// 根据环境变量选择截图来源，未设置时使用真实屏幕
+pub fn source_from_env() -> Result<Arc<dyn ScreenSource>> {
+    match std::env::var(SCREEN_SOURCE_ENV) {
+        Ok(spec) if !spec.trim().is_empty() => parse_source_spec(spec.trim()),
+        _ => Ok(Arc::new(SystemScreenSource)),
+    }
+}
+
+fn parse_source_spec(spec: &str) -> Result<Arc<dyn ScreenSource>> {
+    if let Some(path) = spec.strip_prefix("file:") {
+        return Ok(Arc::new(ImageScreenSource::from_file(path)?));
+    }
+    
+    if let Some(size) = spec.strip_prefix("synthetic:") {
+        let (w, h) = size
+            .split_once('x')
+            .and_then(|(w, h)| Some((w.trim().parse::<u32>().ok()?, h.trim().parse::<u32>().ok()?)))
+            .filter(|(w, h)| *w > 0 && *h > 0)
+            .ok_or_else(|| anyhow::anyhow!("无效的屏幕尺寸: {}", size))?;
+        return Ok(Arc::new(ImageScreenSource::solid(w, h, [255, 255, 255, 255])));
+    }
+    
+    if spec == "system" {
+        return Ok(Arc::new(SystemScreenSource));
+    }
+    
+    Err(anyhow::anyhow!("无法识别的截图来源: {}", spec))
+}
 
 /// 截取所有屏幕并返回截图数据
 pub fn capture_all_screens() -> Result<Vec<ScreenCapture>> {
@@ -12,13 +127,13 @@ pub fn capture_all_screens() -> Result<Vec<ScreenCapture>> {
         let image = screen.capture()?;
         let buffer = image.rgba().to_vec();
         
-        captures.push(ScreenCapture {
-            width: image.width(),
-            height: image.height(),
-            data: buffer,
-            x: screen.display_info.x,
-            y: screen.display_info.y,
-        });
+        captures.push(ScreenCapture::new(
+            image.width(),
+            image.height(),
+            buffer,
+            screen.display_info.x,
+            screen.display_info.y,
+        ));
     }
 
     Ok(captures)
@@ -36,27 +151,48 @@ pub fn capture_primary_screen() -> Result<ScreenCapture> {
     let image = screen.capture()?;
     let buffer = image.rgba().to_vec();
 
-    Ok(ScreenCapture {
-        width: image.width(),
-        height: image.height(),
-        data: buffer,
-        x: screen.display_info.x,
-        y: screen.display_info.y,
-    })
+    Ok(ScreenCapture::new(
+        image.width(),
+        image.height(),
+        buffer,
+        screen.display_info.x,
+        screen.display_info.y,
+    ))
 }
 
-/// 将图像转换为 PNG 格式的 Base64 字符串
-pub fn to_base64_png(capture: &ScreenCapture) -> Result<String> {
-    let img: ImageBuffer<Rgba<u8>, Vec<u8>> =
-        ImageBuffer::from_raw(capture.width, capture.height, capture.data.clone())
-            .ok_or_else(|| anyhow::anyhow!("无法创建图像缓冲区"))?;
+/// 读取剪贴板中的图片
+pub fn clipboard_image() -> Result<ScreenCapture> {
+    let mut clipboard = arboard::Clipboard::new()
+        .map_err(|e| anyhow::anyhow!("无法访问剪贴板: {}", e))?;
+    let image = clipboard.get_image()
+        .map_err(|e| anyhow::anyhow!("剪贴板中没有图片: {}", e))?;
+
+    Ok(ScreenCapture::new(
+        image.width as u32,
+        image.height as u32,
+        image.bytes.into_owned(),
+        0,
+        0,
+    ))
+}
 
+/// 编码为 PNG，`CompressionType::Fast` 适合本机传输（例如通过自定义协议发送给前端）
+pub fn encode_png(capture: &ScreenCapture, compression: CompressionType) -> Result<Vec<u8>> {
+    let view = capture.view()?;
     let mut buffer = Vec::new();
-    let mut cursor = Cursor::new(&mut buffer);
-    
-    img.write_to(&mut cursor, image::ImageOutputFormat::Png)?;
+    PngEncoder::new_with_quality(&mut buffer, compression, FilterType::Adaptive).write_image(
+        view.as_raw(),
+        capture.width,
+        capture.height,
+        ColorType::Rgba8,
+    )?;
+    Ok(buffer)
+}
 
-    Ok(base64::encode(&buffer))
+/// 将图像转换为 PNG 格式的 Base64 字符串
+pub fn to_base64_png(capture: &ScreenCapture) -> Result<String> {
+    let buffer = encode_png(capture, CompressionType::Default)?;
+    Ok(base64::engine::general_purpose::STANDARD.encode(&buffer))
 }
 
 /// 屏幕截图数据
@@ -64,13 +200,80 @@ pub fn to_base64_png(capture: &ScreenCapture) -> Result<String> {
 pub struct ScreenCapture {
     pub width: u32,
     pub height: u32,
-    pub data: Vec<u8>,
+    /// RGBA 像素，由流水线各阶段、输出和识别线程共享，克隆截图不会复制像素
+    pub data: Arc<Vec<u8>>,
     pub x: i32,
     pub y: i32,
 }
 
+/// 借用截图像素的图像，不复制数据
+pub type CaptureView<'a> = ImageBuffer<Rgba<u8>, &'a [u8]>;
+
+impl ScreenCapture {
+    pub fn new(width: u32, height: u32, data: Vec<u8>, x: i32, y: i32) -> Self {
+        Self {
+            width,
+            height,
+            data: Arc::new(data),
+            x,
+            y,
+        }
+    }
+
+    pub fn from_image(image: RgbaImage, x: i32, y: i32) -> Self {
+        Self::new(image.width(), image.height(), image.into_raw(), x, y)
+    }
+
+    /// 以 `image` 图像的形式借用像素，数据长度与尺寸不符时返回错误
+    pub fn view(&self) -> Result<CaptureView<'_>> {
+        ImageBuffer::from_raw(self.width, self.height, self.data.as_slice()).ok_or_else(|| {
+            anyhow::anyhow!(
+                "截图数据长度 {} 与尺寸 {}x{} 不符",
+                self.data.len(),
+                self.width,
+                self.height
+            )
+        })
+    }
+}
+
+/// 最近几张截图编码后的 PNG，前端通过自定义协议按 ID 读取，避免用 Base64 放进 JSON 传输
+///
+/// 协议处理函数在界面线程上执行，所以截图在保存前编码好，读取时只复制数据。
+/// ID 是随机生成的，其他页面无法猜到。
+pub struct CaptureCache {
+    entries: Mutex<VecDeque<(String, Arc<Vec<u8>>)>>,
+    capacity: usize,
+}
+
+impl CaptureCache {
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            entries: Mutex::new(VecDeque::new()),
+            capacity: capacity.max(1),
+        }
+    }
+
+    /// 保存 PNG 并返回 ID，超出容量时丢弃最早的截图
+    pub fn insert(&self, png: Vec<u8>) -> String {
+        let id = format!("{:032x}", rand::random::<u128>());
+        let mut entries = self.entries.lock().unwrap();
+        if entries.len() == self.capacity {
+            entries.pop_front();
+        }
+        entries.push_back((id.clone(), Arc::new(png)));
+        id
+    }
+
+    /// 按 ID 读取 PNG
+    pub fn get(&self, id: &str) -> Option<Arc<Vec<u8>>> {
+        let entries = self.entries.lock().unwrap();
+        entries.iter().find(|(entry_id, _)| entry_id == id).map(|(_, png)| png.clone())
+    }
+}
+
 /// 截图区域
-#[derive(Debug, Clone, serde::Deserialize)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
 pub struct CaptureRegion {
     pub x: u32,
     pub y: u32,
@@ -78,45 +281,309 @@ pub struct CaptureRegion {
     pub height: u32,
 }
 
-/// 从完整截图中裁剪指定区域
-pub fn crop_region(capture: &ScreenCapture, region: &CaptureRegion) -> Result<ScreenCapture> {
-    let img: ImageBuffer<Rgba<u8>, Vec<u8>> =
-        ImageBuffer::from_raw(capture.width, capture.height, capture.data.clone())
-            .ok_or_else(|| anyhow::anyhow!("无法创建图像缓冲区"))?;
+impl CaptureRegion {
+    /// 区域完整位于 `width`x`height` 的图像内
+    pub fn fits_within(&self, width: u32, height: u32) -> bool {
+        self.x.checked_add(self.width).is_some_and(|right| right <= width)
+            && self.y.checked_add(self.height).is_some_and(|bottom| bottom <= height)
+    }
+}
+
+/// 逻辑像素矩形换算为物理像素的左、上、右、下边界
+fn to_physical(x: f64, y: f64, width: f64, height: f64, scale_factor: f64) -> (f64, f64, f64, f64) {
+    let scale = if scale_factor.is_finite() && scale_factor > 0.0 { scale_factor } else { 1.0 };
+    (
+        (x * scale).floor(),
+        (y * scale).floor(),
+        ((x + width) * scale).ceil(),
+        ((y + height) * scale).ceil(),
+    )
+}
+
+/// 裁剪截图失败
+#[derive(Debug, Clone, PartialEq, Eq, Error)]
+pub enum CropError {
+    /// 裁剪区域的宽或高为 0
+    #[error("裁剪区域尺寸无效: {width}x{height}")]
+    EmptyRegion { width: u32, height: u32 },
+    /// 裁剪区域超出图像范围
+    #[error(
+        "裁剪区域 {}x{} at ({}, {}) 超出图像范围 {image_width}x{image_height}",
+        region.width, region.height, region.x, region.y
+    )]
+    OutOfBounds {
+        region: CaptureRegion,
+        image_width: u32,
+        image_height: u32,
+    },
+    /// 截图数据长度与尺寸不符
+    #[error("截图数据长度 {actual} 与尺寸 {width}x{height} 不符")]
+    InvalidBuffer { width: u32, height: u32, actual: usize },
+}
+
+/// 虚拟桌面坐标系中的区域，坐标可以为负（例如位于主屏左侧或上方的显示器）
+#[derive(Debug, Clone, Copy, serde::Deserialize)]
+pub struct ScreenRegion {
+    pub x: i32,
+    pub y: i32,
+    pub width: u32,
+    pub height: u32,
+}
+
+impl ScreenRegion {
+    /// 把窗口内的逻辑像素（前端的 CSS 像素）区域换算为虚拟桌面的物理像素，向外取整以免丢掉边缘的文字
+    ///
+    /// `origin` 是窗口客户区左上角的物理像素坐标，`scale_factor` 是窗口所在显示器的缩放比例。
+    /// 只换算窗口内的偏移：各显示器缩放比例不同时，虚拟桌面坐标不能统一按一个比例换算。
+    pub fn from_window(origin: (i32, i32), x: f64, y: f64, width: f64, height: f64, scale_factor: f64) -> Self {
+        let (left, top, right, bottom) = to_physical(x, y, width, height, scale_factor);
+        Self {
+            x: origin.0 + left as i32,
+            y: origin.1 + top as i32,
+            width: (right - left) as u32,
+            height: (bottom - top) as u32,
+        }
+    }
+}
 
-    let cropped = image::imageops::crop_imm(&img, region.x, region.y, region.width, region.height);
-    let cropped_buffer = cropped.to_image();
+/// 将与区域相交的各屏幕截图拼接成一张图像
+///
+/// 返回的截图原点为区域左上角，区域中不属于任何屏幕的部分填充为透明黑色。
+pub fn stitch_region(screens: &[ScreenCapture], region: &ScreenRegion) -> Result<ScreenCapture> {
+    if region.width == 0 || region.height == 0 {
+        return Err(anyhow::anyhow!("区域尺寸无效: {}x{}", region.width, region.height));
+    }
+    
+    let region_right = region.x as i64 + region.width as i64;
+    let region_bottom = region.y as i64 + region.height as i64;
+    let stride = region.width as usize * 4;
+    let mut data = vec![0u8; stride * region.height as usize];
+    let mut intersected = false;
+    
+    for screen in screens {
+        // 与当前屏幕的交集（虚拟桌面坐标）
+        let left = (region.x as i64).max(screen.x as i64);
+        let top = (region.y as i64).max(screen.y as i64);
+        let right = region_right.min(screen.x as i64 + screen.width as i64);
+        let bottom = region_bottom.min(screen.y as i64 + screen.height as i64);
+        
+        if left >= right || top >= bottom {
+            continue;
+        }
+        intersected = true;
+        
+        let row_bytes = (right - left) as usize * 4;
+        let screen_stride = screen.width as usize * 4;
+        for y in top..bottom {
+            let src = (y - screen.y as i64) as usize * screen_stride + (left - screen.x as i64) as usize * 4;
+            let dst = (y - region.y as i64) as usize * stride + (left - region.x as i64) as usize * 4;
+            data[dst..dst + row_bytes].copy_from_slice(&screen.data[src..src + row_bytes]);
+        }
+    }
+    
+    if !intersected {
+        return Err(anyhow::anyhow!(
+            "区域 {}x{} at ({}, {}) 不在任何屏幕范围内",
+            region.width,
+            region.height,
+            region.x,
+            region.y
+        ));
+    }
+    
+    Ok(ScreenCapture::new(region.width, region.height, data, region.x, region.y))
+}
+
+/// 从截图中裁剪指定区域，区域必须完整位于截图内
+pub fn crop_region(capture: &ScreenCapture, region: &CaptureRegion) -> Result<ScreenCapture, CropError> {
+    if region.width == 0 || region.height == 0 {
+        return Err(CropError::EmptyRegion {
+            width: region.width,
+            height: region.height,
+        });
+    }
+    if !region.fits_within(capture.width, capture.height) {
+        return Err(CropError::OutOfBounds {
+            region: *region,
+            image_width: capture.width,
+            image_height: capture.height,
+        });
+    }
+    let stride = capture.width as usize * 4;
+    if capture.data.len() != stride * capture.height as usize {
+        return Err(CropError::InvalidBuffer {
+            width: capture.width,
+            height: capture.height,
+            actual: capture.data.len(),
+        });
+    }
 
-    Ok(ScreenCapture {
-        width: region.width,
-        height: region.height,
-        data: cropped_buffer.into_raw(),
-        x: capture.x + region.x as i32,
-        y: capture.y + region.y as i32,
-    })
+    // 区域覆盖整张截图时共享像素
+    if region.width == capture.width && region.height == capture.height {
+        return Ok(capture.clone());
+    }
+
+    // 只复制区域内的各行：预处理、PNG 编码、缩略图和区域拼接都按紧密排列的 RGBA 读取 `data`，
+    // 保留带行跨度的视图需要修改所有这些环节，而区域通常远小于整屏，复制的开销相对识别可以忽略
+    let row_bytes = region.width as usize * 4;
+    let mut data = Vec::with_capacity(row_bytes * region.height as usize);
+    for row in region.y..region.y + region.height {
+        let start = row as usize * stride + region.x as usize * 4;
+        data.extend_from_slice(&capture.data[start..start + row_bytes]);
+    }
+
+    Ok(ScreenCapture::new(
+        region.width,
+        region.height,
+        data,
+        capture.x + region.x as i32,
+        capture.y + region.y as i32,
+    ))
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
 
+    /// 纯色屏幕
+    fn solid_screen(x: i32, y: i32, width: u32, height: u32, value: u8) -> ScreenCapture {
+        ScreenCapture::new(width, height, vec![value; (width * height * 4) as usize], x, y)
+    }
+    
     #[test]
-    fn test_capture_primary_screen() {
-        let result = capture_primary_screen();
-        assert!(result.is_ok());
+    fn test_stitch_across_monitors() {
+        // 左侧副屏位于负坐标
+        let screens = vec![
+            solid_screen(0, 0, 100, 100, 10),
+            solid_screen(-80, 20, 80, 60, 200),
+        ];
+        let region = ScreenRegion { x: -10, y: 30, width: 20, height: 10 };
+        
+        let capture = stitch_region(&screens, &region).unwrap();
+        assert_eq!((capture.x, capture.y), (-10, 30));
+        assert_eq!(capture.data.len(), 20 * 10 * 4);
+        // 左半部分来自副屏，右半部分来自主屏
+        assert_eq!(capture.data[0], 200);
+        assert_eq!(capture.data[19 * 4], 10);
+    }
+    
+    #[test]
+    fn test_stitch_outside_screens() {
+        let screens = vec![solid_screen(0, 0, 100, 100, 10)];
+        let region = ScreenRegion { x: 500, y: 500, width: 10, height: 10 };
+        assert!(stitch_region(&screens, &region).is_err());
+    }
+    
+    #[test]
+    fn test_image_source_primary_and_region() {
+        let source = ImageScreenSource::new(vec![
+            solid_screen(0, 0, 100, 100, 10),
+            solid_screen(100, 0, 100, 100, 200),
+        ]);
+        
+        let primary = source.capture_primary().unwrap();
+        assert_eq!((primary.width, primary.height), (100, 100));
+        
+        let region = ScreenRegion { x: 90, y: 0, width: 20, height: 5 };
+        let capture = source.capture_region(&region).unwrap();
+        assert_eq!(capture.data[0], 10);
+        assert_eq!(capture.data[19 * 4], 200);
+    }
+    
+    #[test]
+    fn test_parse_source_spec() {
+        let source = parse_source_spec("synthetic:64x32").unwrap();
+        let capture = source.capture_primary().unwrap();
+        assert_eq!((capture.width, capture.height), (64, 32));
+        assert_eq!(capture.data.len(), 64 * 32 * 4);
         
-        let capture = result.unwrap();
-        assert!(capture.width > 0);
-        assert!(capture.height > 0);
-        assert!(!capture.data.is_empty());
+        assert!(parse_source_spec("synthetic:0x10").is_err());
+        assert!(parse_source_spec("unknown").is_err());
+    }
+
+    #[test]
+    fn test_crop_region_bounds() {
+        let mut capture = solid_screen(10, 20, 100, 50, 0);
+        Arc::make_mut(&mut capture.data)[(5 * 100 + 90) * 4] = 255;
+
+        let region = CaptureRegion { x: 90, y: 5, width: 10, height: 45 };
+        let cropped = crop_region(&capture, &region).unwrap();
+        assert_eq!((cropped.x, cropped.y, cropped.width, cropped.height), (100, 25, 10, 45));
+        assert_eq!(cropped.data.len(), 10 * 45 * 4);
+        assert_eq!(cropped.data[0], 255);
+
+        let region = CaptureRegion { x: 90, y: 0, width: 20, height: 10 };
+        assert!(matches!(crop_region(&capture, &region), Err(CropError::OutOfBounds { .. })));
+
+        let region = CaptureRegion { x: u32::MAX, y: 0, width: 2, height: 1 };
+        assert!(matches!(crop_region(&capture, &region), Err(CropError::OutOfBounds { .. })));
+        let region = CaptureRegion { x: 0, y: 0, width: 0, height: 10 };
+        assert!(matches!(crop_region(&capture, &region), Err(CropError::EmptyRegion { .. })));
+
+        Arc::make_mut(&mut capture.data).pop();
+        let region = CaptureRegion { x: 0, y: 0, width: 1, height: 1 };
+        assert!(matches!(crop_region(&capture, &region), Err(CropError::InvalidBuffer { .. })));
+    }
+
+    #[test]
+    fn test_logical_to_physical() {
+        // 150% 缩放时向外取整
+        let region = ScreenRegion::from_window((0, 0), 10.0, 10.5, 33.0, 20.0, 1.5);
+        assert_eq!((region.x, region.y, region.width, region.height), (15, 15, 50, 31));
+
+        // 主屏 1920x1080 缩放 100%，左侧副屏 2560x1440 缩放 200%，窗口在副屏上：
+        // 窗口原点已是物理像素，只有窗口内的偏移按 2 倍换算
+        let region = ScreenRegion::from_window((-2560, 0), 100.0, 50.0, 200.0, 100.0, 2.0);
+        assert_eq!((region.x, region.y, region.width, region.height), (-2360, 100, 400, 200));
+
+        // 无效的缩放比例按 1 处理
+        let region = ScreenRegion::from_window((5, -5), 1.0, 2.0, 3.0, 4.0, 0.0);
+        assert_eq!((region.x, region.y, region.width, region.height), (6, -3, 3, 4));
+    }
+
+    #[test]
+    fn test_crop_shares_full_region() {
+        let capture = solid_screen(0, 0, 40, 30, 7);
+        let cropped = crop_region(&capture, &CaptureRegion { x: 0, y: 0, width: 40, height: 30 }).unwrap();
+        assert!(Arc::ptr_eq(&capture.data, &cropped.data));
+
+        let view = capture.view().unwrap();
+        assert_eq!(view.dimensions(), (40, 30));
+        assert_eq!(view.get_pixel(39, 29).0, [7, 7, 7, 7]);
+    }
+
+    #[test]
+    fn test_capture_cache() {
+        let cache = CaptureCache::new(2);
+        let first = cache.insert(vec![1]);
+        let second = cache.insert(vec![2]);
+        assert_ne!(first, second);
+        assert_eq!(first.len(), 32);
+        assert_eq!(*cache.get(&first).unwrap(), [1]);
+
+        let third = cache.insert(vec![3]);
+        assert!(cache.get(&first).is_none());
+        assert_eq!(*cache.get(&second).unwrap(), [2]);
+        assert_eq!(*cache.get(&third).unwrap(), [3]);
+        assert!(cache.get("1").is_none());
+    }
+
+    #[test]
+    fn test_encode_png_round_trip() {
+        let capture = ImageScreenSource::solid(16, 8, [10, 20, 30, 255]).capture_primary().unwrap();
+        for compression in [CompressionType::Fast, CompressionType::Default] {
+            let png = encode_png(&capture, compression).unwrap();
+            let decoded = image::load_from_memory(&png).unwrap().to_rgba8();
+            assert_eq!(decoded.dimensions(), (16, 8));
+            assert_eq!(decoded.into_raw(), *capture.data);
+        }
     }
 
     #[test]
     fn test_to_base64() {
-        let capture = capture_primary_screen().unwrap();
+        let capture = ImageScreenSource::solid(16, 16, [0, 0, 0, 255]).capture_primary().unwrap();
         let result = to_base64_png(&capture);
         assert!(result.is_ok());
         assert!(!result.unwrap().is_empty());
     }
 }
-
@@ -0,0 +1,176 @@
+use anyhow::Result;
+use serde::Serialize;
+use std::sync::{Arc, Mutex};
+
+/// 前台窗口信息，用于按应用自动选择配置方案
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct WindowInfo {
+    /// 窗口类名（X11 的 WM_CLASS，例如 "firefox"、"org.wezfurlong.wezterm"）
+    pub class: String,
+    /// 窗口标题
+    pub title: String,
+}
+
+/// 前台窗口查询接口，不同平台各自实现，测试中可以替换为固定窗口
+pub trait ActiveWindowProvider: Send + Sync {
+    /// 当前的前台窗口，无法确定时返回 None
+    fn active_window(&self) -> Result<Option<WindowInfo>>;
+}
+
+/// 通过 EWMH 的 `_NET_ACTIVE_WINDOW` 查询 X11 前台窗口（Wayland 下只能看到 XWayland 程序）
+#[cfg(target_os = "linux")]
+pub struct X11WindowProvider;
+
+#[cfg(target_os = "linux")]
+impl ActiveWindowProvider for X11WindowProvider {
+    fn active_window(&self) -> Result<Option<WindowInfo>> {
+        use x11rb::connection::Connection;
+        use x11rb::protocol::xproto::{Atom, AtomEnum, ConnectionExt};
+
+        let (conn, screen_num) = x11rb::connect(None)?;
+        let root = conn.setup().roots[screen_num].root;
+        let intern = |name: &[u8]| -> Result<Atom> { Ok(conn.intern_atom(false, name)?.reply()?.atom) };
+
+        let active = conn
+            .get_property(false, root, intern(b"_NET_ACTIVE_WINDOW")?, AtomEnum::WINDOW, 0, 1)?
+            .reply()?;
+        let Some(window) = active.value32().and_then(|mut ids| ids.next()).filter(|id| *id != 0) else {
+            return Ok(None);
+        };
+
+        // WM_CLASS 为 "实例名\0类名\0"
+        let class = conn
+            .get_property(false, window, AtomEnum::WM_CLASS, AtomEnum::STRING, 0, 1024)?
+            .reply()?;
+        let class = class
+            .value
+            .split(|b| *b == 0)
+            .rfind(|part| !part.is_empty())
+            .map(|part| String::from_utf8_lossy(part).into_owned())
+            .unwrap_or_default();
+
+        // 优先使用 UTF-8 的 _NET_WM_NAME，旧程序只设置 WM_NAME
+        let title = conn
+            .get_property(false, window, intern(b"_NET_WM_NAME")?, intern(b"UTF8_STRING")?, 0, 1024)?
+            .reply()?;
+        let title = if title.value.is_empty() {
+            conn.get_property(false, window, AtomEnum::WM_NAME, AtomEnum::STRING, 0, 1024)?
+                .reply()?
+                .value
+        } else {
+            title.value
+        };
+
+        Ok(Some(WindowInfo {
+            class,
+            title: String::from_utf8_lossy(&title).into_owned(),
+        }))
+    }
+}
+
+/// 不支持查询前台窗口的平台
+pub struct NoActiveWindow;
+
+impl ActiveWindowProvider for NoActiveWindow {
+    fn active_window(&self) -> Result<Option<WindowInfo>> {
+        Ok(None)
+    }
+}
+
+/// 返回预先设置的窗口，用于测试和脚本
+#[derive(Default)]
+pub struct FakeWindowProvider {
+    window: Mutex<Option<WindowInfo>>,
+}
+
+impl FakeWindowProvider {
+    pub fn new(class: &str, title: &str) -> Self {
+        let provider = Self::default();
+        provider.set(class, title);
+        provider
+    }
+
+    pub fn set(&self, class: &str, title: &str) {
+        *self.window.lock().unwrap() = Some(WindowInfo {
+            class: class.to_string(),
+            title: title.to_string(),
+        });
+    }
+}
+
+impl ActiveWindowProvider for FakeWindowProvider {
+    fn active_window(&self) -> Result<Option<WindowInfo>> {
+        Ok(self.window.lock().unwrap().clone())
+    }
+}
+
+/// 固定前台窗口的环境变量，格式为 `<类名>|<标题>`，用于在没有 X11 的环境中测试规则
+pub const ACTIVE_WINDOW_ENV: &str = "SCREENOCR_ACTIVE_WINDOW";
+
+/// 根据环境变量选择前台窗口查询方式，未设置时使用当前平台的实现
+pub fn provider_from_env() -> Arc<dyn ActiveWindowProvider> {
+    match std::env::var(ACTIVE_WINDOW_ENV) {
+        Ok(spec) if !spec.trim().is_empty() => {
+            let (class, title) = spec.split_once('|').unwrap_or((spec.as_str(), ""));
+            Arc::new(FakeWindowProvider::new(class.trim(), title.trim()))
+        }
+        _ => platform_provider(),
+    }
+}
+
+#[cfg(target_os = "linux")]
+fn platform_provider() -> Arc<dyn ActiveWindowProvider> {
+    Arc::new(X11WindowProvider)
+}
+
+#[cfg(not(target_os = "linux"))]
+fn platform_provider() -> Arc<dyn ActiveWindowProvider> {
+    Arc::new(NoActiveWindow)
+}
+
+/// 不区分大小写的通配符匹配，`*` 匹配任意多个字符，`?` 匹配一个字符
+pub fn wildcard_match(pattern: &str, text: &str) -> bool {
+    let pattern: Vec<char> = pattern.to_lowercase().chars().collect();
+    let text: Vec<char> = text.to_lowercase().chars().collect();
+
+    // 贪心匹配，遇到不匹配时回溯到上一个 `*`
+    let (mut p, mut t) = (0, 0);
+    let mut star: Option<(usize, usize)> = None;
+    while t < text.len() {
+        match pattern.get(p) {
+            Some('*') => {
+                star = Some((p, t));
+                p += 1;
+            }
+            Some(c) if *c == '?' || *c == text[t] => {
+                p += 1;
+                t += 1;
+            }
+            _ => match star {
+                Some((star_p, star_t)) => {
+                    p = star_p + 1;
+                    t = star_t + 1;
+                    star = Some((star_p, star_t + 1));
+                }
+                None => return false,
+            },
+        }
+    }
+    pattern[p..].iter().all(|c| *c == '*')
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_wildcard_match() {
+        assert!(wildcard_match("firefox", "Firefox"));
+        assert!(wildcard_match("*term*", "gnome-terminal-server"));
+        assert!(wildcard_match("*.pdf - *", "论文.pdf - Okular"));
+        assert!(wildcard_match("code?oss", "code-oss"));
+        assert!(wildcard_match("*", ""));
+        assert!(!wildcard_match("firefox", "firefox-esr"));
+        assert!(!wildcard_match("*.pdf", "report.pdf.txt"));
+    }
+}